@@ -0,0 +1,117 @@
+// src/asteroids.rs
+
+use nalgebra_glm::{self as glm, Mat4, Vec3};
+use std::f32::consts::TAU;
+use crate::color::Color;
+use crate::fragment::{CelestialType, Fragment, FragmentOutput};
+use crate::framebuffer::Framebuffer;
+use crate::geometry;
+use crate::random::Rng;
+use crate::renderer::{render_instanced, Instance};
+use crate::shadows::{eclipse_factor, light_direction_at};
+use crate::vertex::Vertex;
+use crate::Uniforms;
+
+const ROCK_SHAPES: usize = 6; // Mallas distintas que se reparten entre todas las rocas
+
+/// Órbita y giro de una roca del cinturón
+pub struct Rock {
+    pub orbit_radius: f32,
+    pub phase: f32,        // Ángulo orbital en t = 0
+    pub height: f32,       // Desplazamiento fuera del plano del cinturón
+    pub size: f32,
+    pub spin_axis: Vec3,
+    pub spin_speed: f32,
+    pub shape: usize,      // Índice de la malla
+    pub seed: u32,
+}
+
+/// Cinturón de asteroides entre dos órbitas, dibujado con instancias
+pub struct AsteroidBelt {
+    pub rocks: Vec<Rock>,
+    pub shapes: Vec<Vec<Vertex>>,
+    pub orbital_speed: f32, // Velocidad angular en el borde interior (rad/s)
+    pub inner_radius: f32,
+}
+
+impl AsteroidBelt {
+    /// Genera `count` rocas entre `inner_radius` y `outer_radius`, con un espesor vertical
+    /// y tamaños en el espacio del cinturón, que `belt_matrix` lleva al mundo
+    pub fn generate(seed: u64, count: usize, inner_radius: f32, outer_radius: f32, thickness: f32, size: (f32, f32)) -> Self {
+        let mut rng = Rng::new(seed);
+        let shapes = (0..ROCK_SHAPES)
+            .map(|i| geometry::rock(seed.wrapping_add(i as u64 * 7919), 1, 0.35))
+            .collect();
+
+        let rocks = (0..count)
+            .map(|i| {
+                // Más densidad hacia el centro del cinturón
+                let t = (rng.next_f32() + rng.next_f32()) * 0.5;
+                Rock {
+                    orbit_radius: inner_radius + (outer_radius - inner_radius) * t,
+                    phase: rng.range(0.0, TAU),
+                    height: rng.range(-0.5, 0.5) * thickness,
+                    // Muchas rocas pequeñas y pocas grandes
+                    size: size.0 + (size.1 - size.0) * rng.next_f32().powi(3),
+                    spin_axis: rng.unit_vector(),
+                    spin_speed: rng.range(-2.0, 2.0),
+                    shape: i % ROCK_SHAPES,
+                    seed: rng.next_u64() as u32,
+                }
+            })
+            .collect();
+
+        AsteroidBelt { rocks, shapes, orbital_speed: 0.12, inner_radius }
+    }
+
+    /// Instancias agrupadas por malla en el instante `time`. `belt_matrix` coloca el
+    /// plano del cinturón (XZ) en el mundo
+    pub fn instances(&self, time: f32, belt_matrix: &Mat4) -> Vec<Vec<Instance>> {
+        let mut groups = vec![Vec::new(); self.shapes.len()];
+        for rock in &self.rocks {
+            // Tercera ley de Kepler: las rocas exteriores van más despacio
+            let speed = self.orbital_speed * (self.inner_radius / rock.orbit_radius).powf(1.5);
+            let angle = rock.phase + time * speed;
+            let position = Vec3::new(rock.orbit_radius * angle.cos(), rock.height, rock.orbit_radius * angle.sin());
+
+            let model_matrix = belt_matrix
+                * glm::translation(&position)
+                * glm::rotation(time * rock.spin_speed, &rock.spin_axis)
+                * glm::scaling(&Vec3::new(rock.size, rock.size, rock.size));
+            groups[rock.shape].push(Instance { model_matrix, seed: rock.seed });
+        }
+        groups
+    }
+
+    pub fn render(&self, framebuffer: &mut Framebuffer, uniforms: &Uniforms, belt_matrix: &Mat4) {
+        for (shape, instances) in self.shapes.iter().zip(self.instances(uniforms.time, belt_matrix)) {
+            render_instanced(framebuffer, uniforms, shape, &instances, CelestialType::Asteroid);
+        }
+    }
+}
+
+/// Roca de asteroide: el tipo (carbonáceo, silicato o metálico) y el brillo salen de la
+/// semilla de la instancia; la luz viene de la estrella si hay una luz puntual
+pub fn asteroid_shader(fragment: &Fragment, uniforms: &Uniforms) -> FragmentOutput {
+    let seed = uniforms.instance_seed;
+    let kind = seed % 10;
+    let base = match kind {
+        0..=5 => Color::new(82, 78, 74),   // Tipo C, oscuro
+        6..=8 => Color::new(150, 126, 98), // Tipo S, rojizo
+        _ => Color::new(160, 160, 168),    // Tipo M, metálico
+    };
+    let albedo = 0.8 + 0.4 * ((seed >> 8) % 256) as f32 / 255.0;
+
+    // Manchas y polvo desplazados por instancia para que no se repitan
+    let offset = ((seed >> 16) % 1024) as f32;
+    let p = fragment.vertex_position.xyz() * 9.0;
+    let speckle = uniforms.noise.get_noise_3d(p.x + offset, p.y, p.z - offset);
+    let color = base * (albedo * (0.85 + 0.25 * speckle));
+
+    let world_position = (uniforms.model_matrix * fragment.vertex_position).xyz();
+    let light_dir = light_direction_at(uniforms, &world_position);
+    let diffuse = fragment.normal.dot(&light_dir).max(0.0) * eclipse_factor(uniforms, &world_position);
+    let ambient = color.blend_multiply(&uniforms.ambient_color) * uniforms.ambient_intensity;
+
+    FragmentOutput::surface(color.blend_multiply(&uniforms.light_color) * diffuse + ambient)
+}
@@ -0,0 +1,199 @@
+// src/atmosphere.rs
+
+use nalgebra_glm::{Vec3, Vec4};
+use std::f32::consts::PI;
+use crate::color::Color;
+use crate::fragment::{Fragment, FragmentOutput};
+use crate::shaders::body_light_direction;
+use crate::Uniforms;
+
+const VIEW_SAMPLES: usize = 12;
+const LIGHT_SAMPLES: usize = 6;
+
+/// Parámetros de dispersión atmosférica. Las distancias están en radios del planeta
+#[derive(Clone, Copy)]
+pub struct AtmosphereSettings {
+    pub radius: f32,                 // Radio exterior de la atmósfera (planeta = 1.0)
+    pub wavelengths: Vec3,           // Longitudes de onda RGB en nm
+    pub rayleigh_depth: f32,         // Espesor óptico vertical de Rayleigh a 550 nm
+    pub rayleigh_scale_height: f32,
+    pub mie_depth: f32,              // Espesor óptico vertical de Mie (aerosoles)
+    pub mie_scale_height: f32,
+    pub mie_anisotropy: f32,         // g de Henyey-Greenstein: > 0 dispersa hacia delante
+    pub mie_color: Vec3,             // Tinte de los aerosoles (polvo, neblina)
+    pub sun_intensity: f32,
+}
+
+// Atmósfera de tipo terrestre: limbo azul y terminador anaranjado
+pub const EARTH_LIKE: AtmosphereSettings = AtmosphereSettings {
+    radius: 1.08,
+    wavelengths: Vec3::new(680.0, 550.0, 440.0),
+    rayleigh_depth: 0.12,
+    rayleigh_scale_height: 0.02,
+    mie_depth: 0.01,
+    mie_scale_height: 0.01,
+    mie_anisotropy: 0.76,
+    mie_color: Vec3::new(1.0, 1.0, 1.0),
+    sun_intensity: 16.0,
+};
+
+// Atmósfera tenue y polvorienta
+pub const DUSTY: AtmosphereSettings = AtmosphereSettings {
+    radius: 1.06,
+    wavelengths: Vec3::new(680.0, 550.0, 440.0),
+    rayleigh_depth: 0.02,
+    rayleigh_scale_height: 0.02,
+    mie_depth: 0.04,
+    mie_scale_height: 0.015,
+    mie_anisotropy: 0.65,
+    mie_color: Vec3::new(1.0, 0.7, 0.45),
+    sun_intensity: 12.0,
+};
+
+// Neblina alta de un gigante gaseoso
+pub const GAS_GIANT_HAZE: AtmosphereSettings = AtmosphereSettings {
+    radius: 1.05,
+    wavelengths: Vec3::new(680.0, 550.0, 440.0),
+    rayleigh_depth: 0.05,
+    rayleigh_scale_height: 0.015,
+    mie_depth: 0.03,
+    mie_scale_height: 0.01,
+    mie_anisotropy: 0.7,
+    mie_color: Vec3::new(1.0, 0.9, 0.75),
+    sun_intensity: 10.0,
+};
+
+impl AtmosphereSettings {
+    /// Coeficientes de Rayleigh por canal: proporcionales a 1/λ⁴
+    pub fn rayleigh_coefficients(&self) -> Vec3 {
+        let beta = |wavelength: f32| {
+            (550.0 / wavelength).powi(4) * self.rayleigh_depth / self.rayleigh_scale_height
+        };
+        Vec3::new(beta(self.wavelengths.x), beta(self.wavelengths.y), beta(self.wavelengths.z))
+    }
+
+    pub fn mie_coefficient(&self) -> f32 {
+        self.mie_depth / self.mie_scale_height
+    }
+
+    /// Luz dispersada hacia el observador a lo largo del rayo `origin + t * direction`,
+    /// con el planeta (radio 1) en el origen y la luz llegando desde `light_dir`
+    pub fn scatter(&self, origin: &Vec3, direction: &Vec3, light_dir: &Vec3) -> Vec3 {
+        let Some((t_enter, t_exit)) = ray_sphere(origin, direction, self.radius) else {
+            return Vec3::zeros();
+        };
+        let t_start = t_enter.max(0.0);
+        let mut t_end = t_exit;
+        if let Some((t_ground, _)) = ray_sphere(origin, direction, 1.0) {
+            if t_ground > 0.0 {
+                t_end = t_end.min(t_ground);
+            }
+        }
+        if t_end <= t_start {
+            return Vec3::zeros();
+        }
+
+        let beta_rayleigh = self.rayleigh_coefficients();
+        let beta_mie = self.mie_coefficient();
+        let step = (t_end - t_start) / VIEW_SAMPLES as f32;
+
+        let mut depth_rayleigh = 0.0;
+        let mut depth_mie = 0.0;
+        let mut sum_rayleigh = Vec3::zeros();
+        let mut sum_mie = Vec3::zeros();
+
+        for i in 0..VIEW_SAMPLES {
+            let position = origin + direction * (t_start + (i as f32 + 0.5) * step);
+            let height = position.magnitude() - 1.0;
+            let density_rayleigh = (-height / self.rayleigh_scale_height).exp() * step;
+            let density_mie = (-height / self.mie_scale_height).exp() * step;
+            depth_rayleigh += density_rayleigh;
+            depth_mie += density_mie;
+
+            // Los puntos a la sombra del planeta no reciben luz del sol
+            let Some((light_rayleigh, light_mie)) = self.light_depth(&position, light_dir) else {
+                continue;
+            };
+
+            let tau = beta_rayleigh * (depth_rayleigh + light_rayleigh)
+                + Vec3::repeat(beta_mie * 1.1 * (depth_mie + light_mie));
+            let attenuation = tau.map(|t| (-t).exp());
+
+            sum_rayleigh += attenuation * density_rayleigh;
+            sum_mie += attenuation * density_mie;
+        }
+
+        let mu = direction.dot(light_dir);
+        let phase_rayleigh = 3.0 / (16.0 * PI) * (1.0 + mu * mu);
+        let g = self.mie_anisotropy;
+        let phase_mie = 3.0 / (8.0 * PI) * ((1.0 - g * g) * (1.0 + mu * mu))
+            / ((2.0 + g * g) * (1.0 + g * g - 2.0 * g * mu).powf(1.5));
+
+        (sum_rayleigh.component_mul(&beta_rayleigh) * phase_rayleigh
+            + sum_mie.component_mul(&self.mie_color) * beta_mie * phase_mie)
+            * self.sun_intensity
+    }
+
+    // Espesor óptico desde `position` hasta el borde de la atmósfera en dirección a la luz
+    fn light_depth(&self, position: &Vec3, light_dir: &Vec3) -> Option<(f32, f32)> {
+        if let Some((t_ground, _)) = ray_sphere(position, light_dir, 1.0) {
+            if t_ground > 0.0 {
+                return None;
+            }
+        }
+
+        let (_, t_exit) = ray_sphere(position, light_dir, self.radius)?;
+        let step = t_exit / LIGHT_SAMPLES as f32;
+        let mut depth_rayleigh = 0.0;
+        let mut depth_mie = 0.0;
+
+        for i in 0..LIGHT_SAMPLES {
+            let sample = position + light_dir * ((i as f32 + 0.5) * step);
+            let height = sample.magnitude() - 1.0;
+            depth_rayleigh += (-height / self.rayleigh_scale_height).exp() * step;
+            depth_mie += (-height / self.mie_scale_height).exp() * step;
+        }
+
+        Some((depth_rayleigh, depth_mie))
+    }
+}
+
+/// Intersección de un rayo con una esfera centrada en el origen: (t_entrada, t_salida)
+pub fn ray_sphere(origin: &Vec3, direction: &Vec3, radius: f32) -> Option<(f32, f32)> {
+    let b = origin.dot(direction);
+    let c = origin.dot(origin) - radius * radius;
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let root = discriminant.sqrt();
+    Some((-b - root, -b + root))
+}
+
+/// Shader de la capa de atmósfera. Se dibuja sobre una esfera unitaria escalada al radio
+/// exterior de la atmósfera, con mezcla aditiva sobre el planeta ya renderizado
+pub fn atmosphere_shader(fragment: &Fragment, uniforms: &Uniforms) -> FragmentOutput {
+    let Some(settings) = &uniforms.atmosphere else {
+        return FragmentOutput::surface(Color::black());
+    };
+
+    // Cámara en el espacio de la capa (radio 1), luego en radios del planeta
+    let inverse_model = uniforms.model_matrix.try_inverse().unwrap_or_default();
+    let eye = inverse_model * Vec4::new(uniforms.camera_position.x, uniforms.camera_position.y, uniforms.camera_position.z, 1.0);
+    let eye = eye.xyz() * settings.radius;
+    let position = fragment.vertex_position.xyz().normalize() * settings.radius;
+
+    // Solo las caras visibles de la capa; desde dentro de la atmósfera, las traseras
+    let facing = position.dot(&(eye - position)) > 0.0;
+    let inside = eye.magnitude() < settings.radius;
+    if facing == inside {
+        return FragmentOutput::surface(Color::black());
+    }
+
+    let direction = (position - eye).normalize();
+    let light = settings.scatter(&eye, &direction, &body_light_direction(uniforms));
+
+    // Exposición exponencial para no saturar el limbo
+    let to_channel = |value: f32| ((1.0 - (-value).exp()) * 255.0).round() as u8;
+    FragmentOutput::surface(Color::new(to_channel(light.x), to_channel(light.y), to_channel(light.z)))
+}
@@ -1,95 +1,139 @@
 // src/camera.rs
 
-use nalgebra_glm::{Vec3, rotate_vec3};
-use std::f32::consts::PI;
+use nalgebra::{Rotation3, Unit, UnitQuaternion};
+use nalgebra_glm::Vec3;
+use crate::projection::Projection;
 
-/// Estructura que representa la cámara en el espacio 3D
+/// Estructura que representa la cámara en el espacio 3D. La orientación se guarda como
+/// cuaternión (sin límites de ángulos) y el ojo está a `distance` del centro, hacia atrás
 pub struct Camera {
-    pub eye: Vec3,
-    pub center: Vec3,
-    pub up: Vec3,
-    pub has_changed: bool,
+    center: Vec3,
+    distance: f32,
+    orientation: UnitQuaternion<f32>, // De espacio de cámara (-z delante, y arriba) al mundo
+    pub projection: Projection,
 }
 
 impl Camera {
     /// Crea una nueva cámara
     pub fn new(eye: Vec3, center: Vec3, up: Vec3) -> Self {
-        Camera {
-            eye,
+        let mut camera = Camera {
             center,
-            up,
-            has_changed: true,
-        }
+            distance: 1.0,
+            orientation: UnitQuaternion::identity(),
+            projection: Projection::new(),
+        };
+        camera.set_look_at(eye, center, up);
+        camera
     }
 
-    /// Cambia la base de la cámara (no utilizado actualmente)
-    pub fn basis_change(&self, vector: &Vec3) -> Vec3 {
-        let forward = (self.center - self.eye).normalize();
-        let right = forward.cross(&self.up).normalize();
-        let up = right.cross(&forward).normalize();
+    pub fn eye(&self) -> Vec3 {
+        self.center - self.forward() * self.distance
+    }
 
-        let rotated = 
-            vector.x * right +
-            vector.y * up +
-            -vector.z * forward;
+    pub fn center(&self) -> Vec3 {
+        self.center
+    }
 
-        rotated.normalize()
+    pub fn up(&self) -> Vec3 {
+        self.orientation * Vec3::new(0.0, 1.0, 0.0)
     }
 
-    /// Orbita la cámara alrededor del punto central con cambios en yaw y pitch
-    pub fn orbit(&mut self, delta_yaw: f32, delta_pitch: f32) {
-        let radius_vector = self.eye - self.center;
-        let radius = radius_vector.magnitude();
+    /// Distancia del ojo al centro
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
 
-        let current_yaw = radius_vector.z.atan2(radius_vector.x);
+    pub fn forward(&self) -> Vec3 {
+        self.orientation * Vec3::new(0.0, 0.0, -1.0)
+    }
 
-        let radius_xz = (radius_vector.x.powi(2) + radius_vector.z.powi(2)).sqrt();
-        let current_pitch = (-radius_vector.y).atan2(radius_xz);
+    /// Coloca la cámara en `eye` mirando a `center`; `up` solo fija el giro alrededor de la vista
+    pub fn set_look_at(&mut self, eye: Vec3, center: Vec3, up: Vec3) {
+        let offset = center - eye;
+        let distance = offset.magnitude();
+        if distance > 1e-6 {
+            let forward = offset / distance;
+            let mut right = forward.cross(&up);
+            if right.magnitude() < 1e-6 {
+                // `up` paralelo a la vista: se conserva el giro actual
+                right = forward.cross(&self.up());
+            }
+            let right = right.normalize();
+            let up = right.cross(&forward);
+            self.orientation = UnitQuaternion::from_rotation_matrix(&Rotation3::from_basis_unchecked(&[right, up, -forward]));
+            self.distance = distance;
+        }
+        self.center = center;
+    }
 
-        let new_yaw = (current_yaw + delta_yaw) % (2.0 * PI);
-        let new_pitch = (current_pitch + delta_pitch).clamp(-PI / 2.0 + 0.1, PI / 2.0 - 0.1);
+    /// Mueve el centro conservando la orientación y la distancia (el ojo lo acompaña)
+    pub fn set_center(&mut self, center: Vec3) {
+        self.center = center;
+    }
 
-        let new_eye = self.center + Vec3::new(
-            radius * new_yaw.cos() * new_pitch.cos(),
-            -radius * new_pitch.sin(),
-            radius * new_yaw.sin() * new_pitch.cos()
-        );
+    /// Desplaza el ojo y el centro a la vez
+    pub fn translate(&mut self, offset: Vec3) {
+        self.center += offset;
+    }
 
-        self.eye = new_eye;
-        self.has_changed = true;
+    /// Pasa un vector del espacio de la cámara (x derecha, y arriba, -z delante) al mundo
+    pub fn basis_change(&self, vector: &Vec3) -> Vec3 {
+        (self.orientation * vector).normalize()
+    }
+
+    /// Orbita la cámara alrededor del punto central (arcball): el giro es sobre los ejes
+    /// propios de la cámara, así que se puede pasar por encima de los polos
+    pub fn orbit(&mut self, delta_yaw: f32, delta_pitch: f32) {
+        let yaw = UnitQuaternion::from_axis_angle(&Vec3::y_axis(), -delta_yaw);
+        let pitch = UnitQuaternion::from_axis_angle(&Vec3::x_axis(), delta_pitch);
+        self.orientation *= yaw * pitch;
+        self.orientation.renormalize(); // Evita que se acumule error numérico
+    }
+
+    /// Gira la cámara alrededor de la dirección de vista
+    pub fn roll(&mut self, angle: f32) {
+        let roll = UnitQuaternion::from_axis_angle(&Vec3::z_axis(), angle);
+        self.orientation *= roll;
+        self.orientation.renormalize();
+    }
+
+    /// Trackball: arrastrar de `from` a `to` (coordenadas de pantalla normalizadas en [-1, 1],
+    /// y hacia arriba) gira la escena como si fuera una esfera bajo el cursor
+    pub fn trackball(&mut self, from: (f32, f32), to: (f32, f32)) {
+        let a = trackball_point(from);
+        let b = trackball_point(to);
+        let Some(axis) = Unit::try_new(a.cross(&b), 1e-6) else {
+            return;
+        };
+        let angle = a.dot(&b).clamp(-1.0, 1.0).acos();
+        // La escena gira con el cursor, así que la cámara gira al revés
+        let rotation = UnitQuaternion::from_axis_angle(&axis, -angle);
+        self.orientation *= rotation;
+        self.orientation.renormalize();
     }
 
     /// Realiza un zoom in o out moviendo la cámara hacia o desde el punto central
     pub fn zoom(&mut self, delta: f32) {
-        let direction = (self.center - self.eye).normalize();
-        self.eye += direction * delta;
-        self.has_changed = true;
+        self.distance = (self.distance - delta).max(1e-3);
     }
 
-    /// Mueve el punto central de la cámara (no es necesario en la funcionalidad actual)
+    /// Gira el punto central alrededor del ojo (mirar alrededor sin moverse)
     pub fn move_center(&mut self, direction: Vec3) {
-        let radius_vector = self.center - self.eye;
-        let radius = radius_vector.magnitude();
-
+        let eye = self.eye();
         let angle_x = direction.x * 0.05; // Ajusta este factor para controlar la velocidad de rotación
         let angle_y = direction.y * 0.05;
 
-        let rotated = rotate_vec3(&radius_vector, angle_x, &Vec3::new(0.0, 1.0, 0.0));
-
-        let right = rotated.cross(&self.up).normalize();
-        let final_rotated = rotate_vec3(&rotated, angle_y, &right);
-
-        self.center = self.eye + final_rotated.normalize() * radius;
-        self.has_changed = true;
+        let yaw = UnitQuaternion::from_axis_angle(&Vec3::y_axis(), angle_x);
+        let pitch = UnitQuaternion::from_axis_angle(&Vec3::x_axis(), angle_y);
+        self.orientation *= yaw * pitch;
+        self.orientation.renormalize();
+        self.center = eye + self.forward() * self.distance;
     }
+}
 
-    /// Verifica si la cámara ha cambiado
-    pub fn check_if_changed(&mut self) -> bool {
-        if self.has_changed {
-            self.has_changed = false;
-            true
-        } else {
-            false
-        }
-    }
+// Proyecta un punto de pantalla sobre la esfera del trackball (hipérbola fuera de ella)
+fn trackball_point((x, y): (f32, f32)) -> Vec3 {
+    let d2 = x * x + y * y;
+    let z = if d2 <= 0.5 { (1.0 - d2).sqrt() } else { 0.5 / d2.sqrt() };
+    Vec3::new(x, y, z).normalize()
 }
@@ -0,0 +1,237 @@
+// src/camera_path.rs
+
+use nalgebra::{Rotation3, UnitQuaternion};
+use nalgebra_glm::Vec3;
+use std::fs;
+use std::io::{self, Write};
+use crate::camera::Camera;
+use crate::origin::{FloatingOrigin, WorldPosition};
+
+/// Estado de la cámara en un instante de la simulación, con posiciones absolutas del
+/// mundo para que no dependan del origen flotante
+#[derive(Clone, Copy)]
+pub struct CameraKeyframe {
+    pub time: f32,
+    pub body: usize, // Índice del cuerpo celeste que se muestra
+    pub eye: WorldPosition,
+    pub center: WorldPosition,
+    pub up: Vec3,
+    pub fov: f32,
+}
+
+impl CameraKeyframe {
+    pub fn from_camera(camera: &Camera, origin: &FloatingOrigin, time: f32, body: usize) -> Self {
+        CameraKeyframe {
+            time,
+            body,
+            eye: origin.to_world(&camera.eye()),
+            center: origin.to_world(&camera.center()),
+            up: camera.up(),
+            fov: camera.projection.fov,
+        }
+    }
+
+    pub fn apply(&self, camera: &mut Camera, origin: &FloatingOrigin) {
+        camera.set_look_at(origin.to_local(&self.eye), origin.to_local(&self.center), self.up);
+        camera.projection.fov = self.fov;
+    }
+
+    // Orientación como cuaternión: columnas derecha, arriba y -delante
+    fn orientation(&self) -> UnitQuaternion<f32> {
+        let forward = (self.center - self.eye).cast::<f32>().normalize();
+        let right = forward.cross(&self.up).normalize();
+        let up = right.cross(&forward);
+        UnitQuaternion::from_rotation_matrix(&Rotation3::from_basis_unchecked(&[right, up, -forward]))
+    }
+}
+
+/// Recorrido de la cámara por fotogramas clave. Se guarda como texto, una línea por clave:
+/// `tiempo cuerpo ojo.x ojo.y ojo.z centro.x centro.y centro.z arriba.x arriba.y arriba.z fov`
+#[derive(Default)]
+pub struct CameraPath {
+    pub keyframes: Vec<CameraKeyframe>,
+}
+
+impl CameraPath {
+    pub fn new() -> Self {
+        CameraPath { keyframes: Vec::new() }
+    }
+
+    /// Añade una clave; deben llegar en orden de tiempo
+    pub fn push(&mut self, keyframe: CameraKeyframe) {
+        self.keyframes.push(keyframe);
+    }
+
+    pub fn save(&self, filename: &str) -> io::Result<()> {
+        let mut file = fs::File::create(filename)?;
+        writeln!(file, "# tiempo cuerpo ojo(xyz) centro(xyz) arriba(xyz) fov")?;
+        for k in &self.keyframes {
+            writeln!(
+                file,
+                "{} {} {} {} {} {} {} {} {} {} {} {}",
+                k.time, k.body, k.eye.x, k.eye.y, k.eye.z, k.center.x, k.center.y, k.center.z, k.up.x, k.up.y, k.up.z, k.fov,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Carga un recorrido; se ignoran las líneas vacías y los comentarios con `#`
+    pub fn load(filename: &str) -> io::Result<Self> {
+        let invalid = |line: usize| io::Error::new(io::ErrorKind::InvalidData, format!("{}: línea {} no válida", filename, line + 1));
+
+        let mut path = CameraPath::new();
+        for (number, line) in fs::read_to_string(filename)?.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let values: Vec<f64> = line
+                .split_whitespace()
+                .map(|value| value.parse().map_err(|_| invalid(number)))
+                .collect::<io::Result<_>>()?;
+            if values.len() != 12 {
+                return Err(invalid(number));
+            }
+            path.push(CameraKeyframe {
+                time: values[0] as f32,
+                body: values[1] as usize,
+                eye: WorldPosition::new(values[2], values[3], values[4]),
+                center: WorldPosition::new(values[5], values[6], values[7]),
+                up: Vec3::new(values[8] as f32, values[9] as f32, values[10] as f32),
+                fov: values[11] as f32,
+            });
+        }
+        if path.keyframes.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{}: sin fotogramas clave", filename)));
+        }
+        Ok(path)
+    }
+
+    pub fn start_time(&self) -> f32 {
+        self.keyframes.first().map_or(0.0, |k| k.time)
+    }
+
+    pub fn end_time(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |k| k.time)
+    }
+
+    /// Cámara en el instante `time`: posición con Catmull-Rom, orientación con slerp y
+    /// distancia al centro y fov lineales. Fuera del recorrido se usa la clave del extremo
+    pub fn sample(&self, time: f32) -> Option<CameraKeyframe> {
+        let keys = &self.keyframes;
+        let last = keys.len().checked_sub(1)?;
+        if time <= keys[0].time || last == 0 {
+            return Some(keys[0]);
+        }
+        if time >= keys[last].time {
+            return Some(keys[last]);
+        }
+
+        // Segmento [i, i + 1] que contiene el instante
+        let i = keys.partition_point(|k| k.time <= time) - 1;
+        let (k1, k2) = (&keys[i], &keys[i + 1]);
+        let span = k2.time - k1.time;
+        let t = if span > 0.0 { (time - k1.time) / span } else { 1.0 };
+
+        // Los extremos se repiten para que la curva pase por la primera y última clave
+        let k0 = &keys[i.saturating_sub(1)];
+        let k3 = &keys[(i + 2).min(last)];
+        let eye = catmull_rom(&k0.eye, &k1.eye, &k2.eye, &k3.eye, t as f64);
+
+        let orientation = k1.orientation().slerp(&k2.orientation(), t);
+        let forward = (orientation * Vec3::new(0.0, 0.0, -1.0)).cast::<f64>();
+        let up = orientation * Vec3::new(0.0, 1.0, 0.0);
+        let distance = lerp((k1.center - k1.eye).magnitude(), (k2.center - k2.eye).magnitude(), t as f64);
+
+        Some(CameraKeyframe {
+            time,
+            body: k1.body,
+            eye,
+            center: eye + forward * distance,
+            up,
+            fov: lerp(k1.fov as f64, k2.fov as f64, t as f64) as f32,
+        })
+    }
+}
+
+// Spline de Catmull-Rom uniforme entre p1 y p2
+fn catmull_rom(p0: &WorldPosition, p1: &WorldPosition, p2: &WorldPosition, p3: &WorldPosition, t: f64) -> WorldPosition {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (p1 * 2.0 + (p2 - p0) * t + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2 + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3) * 0.5
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyframe(time: f32, x: f64, y: f64, z: f64) -> CameraKeyframe {
+        CameraKeyframe {
+            time,
+            body: 2,
+            eye: WorldPosition::new(x, y, z),
+            center: WorldPosition::new(x, y, z - 10.0),
+            up: Vec3::new(0.0, 1.0, 0.0),
+            fov: 45.0,
+        }
+    }
+
+    fn assert_close(a: &WorldPosition, b: &WorldPosition, tolerance: f64) {
+        assert!((a - b).magnitude() <= tolerance, "{:?} != {:?} (tolerancia {})", a, b, tolerance);
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let mut path = CameraPath::new();
+        path.push(keyframe(0.0, 1.0e7, 0.5, -3.25));
+        path.push(keyframe(1.5, 1.0e7 + 123.456, 2.0, -1.0));
+
+        let filename = std::env::temp_dir().join(format!("camera_path_{}.txt", std::process::id()));
+        let filename = filename.to_str().unwrap();
+        path.save(filename).unwrap();
+        let loaded = CameraPath::load(filename);
+        fs::remove_file(filename).unwrap();
+
+        // Los valores se escriben con todos sus dígitos y se leen idénticos
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.keyframes.len(), path.keyframes.len());
+        for (a, b) in loaded.keyframes.iter().zip(&path.keyframes) {
+            assert_eq!(a.time, b.time);
+            assert_eq!(a.body, b.body);
+            assert_eq!(a.eye, b.eye);
+            assert_eq!(a.center, b.center);
+            assert_eq!(a.up, b.up);
+            assert_eq!(a.fov, b.fov);
+        }
+    }
+
+    #[test]
+    fn sample_passes_through_keyframes() {
+        let mut path = CameraPath::new();
+        path.push(keyframe(0.0, 0.0, 0.0, 0.0));
+        path.push(keyframe(1.0, 4.0, 1.0, 0.0));
+        path.push(keyframe(2.0, 8.0, -1.0, 2.0));
+        path.push(keyframe(3.0, 12.0, 0.0, 0.0));
+
+        for key in &path.keyframes {
+            let sample = path.sample(key.time).unwrap();
+            assert_close(&sample.eye, &key.eye, 1e-9);
+            assert_close(&sample.center, &key.center, 1e-4);
+        }
+
+        // Fuera del recorrido se queda en la clave del extremo
+        assert_close(&path.sample(-1.0).unwrap().eye, &path.keyframes[0].eye, 0.0);
+        assert_close(&path.sample(5.0).unwrap().eye, &path.keyframes[3].eye, 0.0);
+    }
+
+    #[test]
+    fn catmull_rom_is_linear_on_evenly_spaced_points() {
+        let points: Vec<WorldPosition> = (0..4).map(|i| WorldPosition::new(i as f64 * 3.0, 1.0, -(i as f64))).collect();
+        let middle = catmull_rom(&points[0], &points[1], &points[2], &points[3], 0.5);
+        assert_close(&middle, &((points[1] + points[2]) * 0.5), 1e-12);
+    }
+}
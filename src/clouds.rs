@@ -0,0 +1,59 @@
+// src/clouds.rs
+
+use nalgebra_glm::Vec3;
+use crate::color::Color;
+use crate::shaders::smoothstep;
+use crate::Uniforms;
+
+/// Capa de nubes independiente del terreno: gira a su propia velocidad
+/// y proyecta sombras sobre la superficie
+pub struct CloudLayer {
+    pub color: Color,
+    pub scale: f32,            // Escala del ruido 3D
+    pub rotation_speed: f32,   // Velocidad angular propia (rad/s) alrededor del eje y
+    pub evolution_speed: f32,  // Velocidad a la que cambia la forma de las nubes
+    pub coverage: f32,         // Fracción del cielo cubierta, en [0, 1]
+    pub density: f32,          // Opacidad máxima de una nube
+    pub altitude: f32,         // Altura de la capa sobre la superficie, en radios del cuerpo
+    pub shadow_strength: f32,  // Oscurecimiento máximo de la sombra sobre el suelo
+}
+
+impl CloudLayer {
+    /// Opacidad de la capa en la dirección `direction` (espacio del cuerpo, normalizada)
+    pub fn cover(&self, uniforms: &Uniforms, direction: &Vec3) -> f32 {
+        if self.coverage <= 0.0 {
+            return 0.0;
+        }
+
+        let angle = uniforms.time * self.rotation_speed;
+        let (sin_a, cos_a) = angle.sin_cos();
+        let rotated = Vec3::new(
+            direction.x * cos_a - direction.z * sin_a,
+            direction.y,
+            direction.x * sin_a + direction.z * cos_a,
+        );
+
+        // Ruido 3D: sin rayas ni simetrías como con el ruido 2D
+        let evolution = uniforms.time * self.evolution_speed;
+        let value = uniforms.noise.get_noise_3d(
+            rotated.x * self.scale + evolution,
+            rotated.y * self.scale,
+            rotated.z * self.scale - evolution,
+        );
+        let value = (value + 1.0) * 0.5;
+
+        let threshold = 1.0 - self.coverage;
+        smoothstep(threshold, threshold + 0.25, value) * self.density
+    }
+
+    /// Atenuación de la luz directa en `direction` por la nube que la tapa a lo largo
+    /// de la dirección de la luz (`light_dir` en espacio del cuerpo). 1.0 = sin sombra
+    pub fn shadow(&self, uniforms: &Uniforms, direction: &Vec3, light_dir: &Vec3) -> f32 {
+        if self.shadow_strength <= 0.0 {
+            return 1.0;
+        }
+
+        let shadow_caster = (direction + light_dir * self.altitude).normalize();
+        1.0 - self.cover(uniforms, &shadow_caster) * self.shadow_strength
+    }
+}
@@ -1,6 +1,7 @@
 use std::fmt;
 use std::ops::Add;
 use std::ops::Mul;
+use nalgebra_glm::Vec3;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Color {
@@ -25,10 +26,46 @@ impl Color {
         Color { r: 0, g: 0, b: 0 }
     }
 
-    pub fn to_hex(&self) -> u32 {
+    pub fn to_hex(self) -> u32 {
         ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
     }
 
+    // Color desde componentes en [0, 1]
+    pub fn from_unit(r: f32, g: f32, b: f32) -> Self {
+        let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        Color::new(channel(r), channel(g), channel(b))
+    }
+
+    /// Color aproximado de un cuerpo negro a la temperatura dada en Kelvin
+    /// (aproximación de Tanner Helland, válida entre 1000 K y 40000 K)
+    pub fn from_temperature(kelvin: f32) -> Self {
+        let t = kelvin.clamp(1000.0, 40000.0) / 100.0;
+
+        let r = if t <= 66.0 { 255.0 } else { 329.698_73 * (t - 60.0).powf(-0.133_204_76) };
+        let g = if t <= 66.0 {
+            99.470_8 * t.ln() - 161.119_57
+        } else {
+            288.122_16 * (t - 60.0).powf(-0.075_514_85)
+        };
+        let b = if t >= 66.0 {
+            255.0
+        } else if t <= 19.0 {
+            0.0
+        } else {
+            138.517_73 * (t - 10.0).ln() - 305.044_8
+        };
+
+        Color::from_unit(r / 255.0, g / 255.0, b / 255.0)
+    }
+
+    /// Color desde HSV: tono en grados, saturación y valor en [0, 1]
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let chroma = value * saturation;
+        let (r, g, b) = hue_to_rgb(hue, chroma);
+        let m = value - chroma;
+        Color::from_unit(r + m, g + m, b + m)
+    }
+
     // Interpolación lineal entre dos colores
     pub fn lerp(&self, other: &Color, t: f32) -> Self {
         let t = t.clamp(0.0, 1.0);
@@ -39,14 +76,6 @@ impl Color {
         }
     }
 
-    pub fn is_black(&self) -> bool {
-        self.r == 0 && self.g == 0 && self.b == 0 
-    }
-
-    pub fn blend_normal(&self, blend: &Color) -> Color {
-        if blend.is_black() { *self } else { *blend }
-    }
-    
     pub fn blend_multiply(&self, blend: &Color) -> Color {
         Color::new(
             ((self.r as f32 * blend.r as f32) / 255.0) as u8,
@@ -64,12 +93,98 @@ impl Color {
     }
     
     pub fn blend_subtract(&self, blend: &Color) -> Color {
-        let r = (self.r as i16 - blend.r as i16).max(0).min(255) as u8;
-        let g = (self.g as i16 - blend.g as i16).max(0).min(255) as u8;
-        let b = (self.b as i16 - blend.b as i16).max(0).min(255) as u8;
+        let r = (self.r as i16 - blend.r as i16).clamp(0, 255) as u8;
+        let g = (self.g as i16 - blend.g as i16).clamp(0, 255) as u8;
+        let b = (self.b as i16 - blend.b as i16).clamp(0, 255) as u8;
 
         Color::new(r, g, b)
     }
+
+    /// Color desde HSL: tono en grados, saturación y luminosidad en [0, 1]
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Self {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let (r, g, b) = hue_to_rgb(hue, chroma);
+        let m = lightness - chroma * 0.5;
+        Color::from_unit(r + m, g + m, b + m)
+    }
+
+    /// Devuelve (tono en grados, saturación, valor)
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let (hue, max, min) = self.hue_max_min();
+        let saturation = if max > 0.0 { (max - min) / max } else { 0.0 };
+        (hue, saturation, max)
+    }
+
+    /// Devuelve (tono en grados, saturación, luminosidad)
+    pub fn to_hsl(self) -> (f32, f32, f32) {
+        let (hue, max, min) = self.hue_max_min();
+        let lightness = (max + min) * 0.5;
+        let saturation = if max == min { 0.0 } else { (max - min) / (1.0 - (2.0 * lightness - 1.0).abs()) };
+        (hue, saturation, lightness)
+    }
+
+    /// Componentes en espacio lineal (deshace la curva sRGB)
+    pub fn to_linear(self) -> Vec3 {
+        Vec3::new(
+            srgb_to_linear(self.r as f32 / 255.0),
+            srgb_to_linear(self.g as f32 / 255.0),
+            srgb_to_linear(self.b as f32 / 255.0),
+        )
+    }
+
+    /// Color sRGB desde componentes lineales
+    pub fn from_linear(linear: Vec3) -> Self {
+        Color::from_unit(linear_to_srgb(linear.x), linear_to_srgb(linear.y), linear_to_srgb(linear.z))
+    }
+
+    // Tono en grados junto con el máximo y el mínimo de los canales en [0, 1]
+    fn hue_max_min(self) -> (f32, f32, f32) {
+        let (r, g, b) = (self.r as f32 / 255.0, self.g as f32 / 255.0, self.b as f32 / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+
+        let hue = if delta == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+        (hue, max, min)
+    }
+}
+
+// Componentes RGB sin el desplazamiento de luminosidad para un tono y croma dados
+fn hue_to_rgb(hue: f32, chroma: f32) -> (f32, f32, f32) {
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    }
+}
+
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.040_45 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
 }
 
 impl Add for Color {
@@ -102,3 +217,78 @@ impl fmt::Display for Color {
         write!(f, "Color(r: {}, g: {}, b: {})", self.r, self.g, self.b)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f32, b: f32, tolerance: f32) {
+        assert!((a - b).abs() <= tolerance, "{} != {} (tolerancia {})", a, b, tolerance);
+    }
+
+    fn assert_channels(color: Color, expected: (u8, u8, u8)) {
+        let (r, g, b) = expected;
+        assert!(
+            color.r.abs_diff(r) <= 1 && color.g.abs_diff(g) <= 1 && color.b.abs_diff(b) <= 1,
+            "{} != Color(r: {}, g: {}, b: {})", color, r, g, b,
+        );
+    }
+
+    #[test]
+    fn hsv_round_trip() {
+        for &(r, g, b) in &[(255, 0, 0), (12, 200, 90), (80, 60, 220), (200, 200, 200), (0, 0, 0)] {
+            let (hue, saturation, value) = Color::new(r, g, b).to_hsv();
+            assert_channels(Color::from_hsv(hue, saturation, value), (r, g, b));
+        }
+    }
+
+    #[test]
+    fn hsl_round_trip() {
+        for &(r, g, b) in &[(255, 0, 0), (12, 200, 90), (80, 60, 220), (200, 200, 200), (255, 255, 255)] {
+            let (hue, saturation, lightness) = Color::new(r, g, b).to_hsl();
+            assert_channels(Color::from_hsl(hue, saturation, lightness), (r, g, b));
+        }
+    }
+
+    #[test]
+    fn hsv_and_hsl_of_known_colors() {
+        let (hue, saturation, value) = Color::new(0, 255, 0).to_hsv();
+        assert_close(hue, 120.0, 1e-3);
+        assert_close(saturation, 1.0, 1e-3);
+        assert_close(value, 1.0, 1e-3);
+
+        let (hue, saturation, lightness) = Color::new(0, 0, 255).to_hsl();
+        assert_close(hue, 240.0, 1e-3);
+        assert_close(saturation, 1.0, 1e-3);
+        assert_close(lightness, 0.5, 1e-3);
+
+        assert_channels(Color::from_hsl(60.0, 1.0, 0.5), (255, 255, 0));
+    }
+
+    #[test]
+    fn srgb_linear_round_trip() {
+        // Valores de referencia de la curva sRGB
+        assert_close(srgb_to_linear(0.5), 0.214, 1e-3);
+        assert_close(linear_to_srgb(0.5), 0.735, 1e-3);
+        assert_close(srgb_to_linear(0.02), 0.02 / 12.92, 1e-6);
+
+        let color = Color::new(30, 128, 250);
+        let linear = color.to_linear();
+        assert_close(linear.x, srgb_to_linear(30.0 / 255.0), 1e-6);
+        assert_channels(Color::from_linear(linear), (30, 128, 250));
+        assert_channels(Color::from_linear(Vec3::new(0.0, 1.0, 0.5)), (0, 255, 188));
+    }
+
+    #[test]
+    fn blackbody_colors() {
+        // Llama roja a 1000 K, blanco a 6600 K y azulado en estrellas calientes
+        assert_channels(Color::from_temperature(1000.0), (255, 68, 0));
+        assert_channels(Color::from_temperature(6600.0), (255, 255, 255));
+        assert_channels(Color::from_temperature(40000.0), (152, 186, 255));
+
+        let cool = Color::from_temperature(3900.0);
+        assert!(cool.r == 255 && cool.g < cool.r && cool.b < cool.g);
+        let hot = Color::from_temperature(15000.0);
+        assert!(hot.b == 255 && hot.r < hot.b);
+    }
+}
@@ -0,0 +1,216 @@
+// src/comet.rs
+
+use nalgebra_glm::{DVec3, Vec2, Vec3};
+use std::f64::consts::TAU;
+use crate::color::Color;
+use crate::fragment::{Fragment, FragmentOutput};
+use crate::shaders::smoothstep;
+use crate::vertex::Vertex;
+use crate::Uniforms;
+
+const TAIL_SEGMENTS: usize = 24;
+
+/// Órbita y aspecto de un cometa. Las distancias orbitales están en unidades astronómicas;
+/// las longitudes de la coma y las colas, en radios del núcleo
+#[derive(Clone, Copy)]
+pub struct CometSettings {
+    pub semi_major_axis: f32,
+    pub eccentricity: f32,
+    pub period: f32,          // Segundos de simulación por órbita
+    pub coma_radius: f32,
+    pub ion_length: f32,      // Longitud de la cola de iones en el perihelio
+    pub dust_length: f32,     // Longitud de la cola de polvo en el perihelio
+    pub dust_curvature: f32,  // Cuánto se curva la cola de polvo hacia atrás en la órbita
+    pub coma_color: Color,
+    pub ion_color: Color,
+    pub dust_color: Color,
+}
+
+pub const HALLEY: CometSettings = CometSettings {
+    semi_major_axis: 4.0,
+    eccentricity: 0.7,
+    period: 60.0,
+    coma_radius: 4.0,
+    ion_length: 14.0,
+    dust_length: 10.0,
+    dust_curvature: 0.35,
+    coma_color: Color::new(170, 230, 210),
+    ion_color: Color::new(90, 150, 255),
+    dust_color: Color::new(255, 232, 190),
+};
+
+/// Estado del cometa en un instante de su órbita
+#[derive(Clone, Copy)]
+pub struct CometState {
+    pub settings: CometSettings,
+    pub activity: f32,           // 1.0 en el perihelio, decae con 1/r²
+    pub sun_direction: Vec3,     // Del núcleo hacia la estrella
+    pub velocity_direction: Vec3,
+}
+
+impl CometSettings {
+    /// Posición respecto a la estrella en UA, en doble precisión para llevarla al mundo
+    pub fn position(&self, time: f32) -> DVec3 {
+        self.orbit(time).0
+    }
+
+    /// Estado en el instante `time`: actividad y direcciones hacia la estrella y de avance
+    pub fn state(&self, time: f32) -> CometState {
+        let (position, velocity) = self.orbit(time);
+        let distance = position.magnitude();
+        let perihelion = self.semi_major_axis as f64 * (1.0 - self.eccentricity as f64);
+        CometState {
+            settings: *self,
+            activity: (perihelion / distance).powi(2) as f32,
+            sun_direction: (-position / distance).cast::<f32>(),
+            velocity_direction: velocity.normalize().cast::<f32>(),
+        }
+    }
+
+    // Posición y velocidad en la órbita (plano XZ, estrella en el foco) resolviendo la
+    // ecuación de Kepler
+    fn orbit(&self, time: f32) -> (DVec3, DVec3) {
+        let e = self.eccentricity as f64;
+        let mean_anomaly = (time as f64 / self.period as f64).fract() * TAU;
+        let mut eccentric_anomaly = mean_anomaly;
+        for _ in 0..8 {
+            eccentric_anomaly -= (eccentric_anomaly - e * eccentric_anomaly.sin() - mean_anomaly)
+                / (1.0 - e * eccentric_anomaly.cos());
+        }
+
+        let a = self.semi_major_axis as f64;
+        let b = a * (1.0 - e * e).sqrt();
+        let (sin_e, cos_e) = eccentric_anomaly.sin_cos();
+        (DVec3::new(a * (cos_e - e), 0.0, b * sin_e), DVec3::new(-a * sin_e, 0.0, b * cos_e))
+    }
+}
+
+/// Mallas de la coma y las colas en espacio del mundo, orientadas hacia la cámara
+pub struct CometGeometry {
+    pub coma: Vec<Vertex>,
+    pub ion_tail: Vec<Vertex>,
+    pub dust_tail: Vec<Vertex>,
+}
+
+impl CometState {
+    pub fn build_geometry(&self, nucleus: Vec3, nucleus_radius: f32, camera_position: Vec3) -> CometGeometry {
+        let settings = &self.settings;
+        let away = -self.sun_direction;
+
+        // Cola de iones: recta, en dirección contraria a la estrella
+        let ion_length = settings.ion_length * nucleus_radius * self.activity.sqrt();
+        let ion_points: Vec<Vec3> = (0..=TAIL_SEGMENTS)
+            .map(|i| nucleus + away * (ion_length * i as f32 / TAIL_SEGMENTS as f32))
+            .collect();
+        let ion_widths: Vec<f32> = (0..=TAIL_SEGMENTS)
+            .map(|i| nucleus_radius * (0.6 + 1.2 * i as f32 / TAIL_SEGMENTS as f32))
+            .collect();
+
+        // Cola de polvo: se curva quedándose atrás respecto al movimiento orbital
+        let dust_length = settings.dust_length * nucleus_radius * self.activity.sqrt();
+        let dust_points: Vec<Vec3> = (0..=TAIL_SEGMENTS)
+            .map(|i| {
+                let s = i as f32 / TAIL_SEGMENTS as f32;
+                nucleus + (away * s - self.velocity_direction * (s * s * settings.dust_curvature)) * dust_length
+            })
+            .collect();
+        let dust_widths: Vec<f32> = (0..=TAIL_SEGMENTS)
+            .map(|i| nucleus_radius * (0.8 + 3.0 * i as f32 / TAIL_SEGMENTS as f32))
+            .collect();
+
+        CometGeometry {
+            coma: billboard(nucleus, settings.coma_radius * nucleus_radius * self.activity.sqrt().max(0.3), camera_position),
+            ion_tail: ribbon(&ion_points, &ion_widths, camera_position),
+            dust_tail: ribbon(&dust_points, &dust_widths, camera_position),
+        }
+    }
+}
+
+// Cuadrado centrado en `center` orientado hacia la cámara, UV en [0, 1]
+fn billboard(center: Vec3, half_size: f32, camera_position: Vec3) -> Vec<Vertex> {
+    let to_camera = (camera_position - center).normalize();
+    let helper = if to_camera.y.abs() > 0.99 { Vec3::new(1.0, 0.0, 0.0) } else { Vec3::new(0.0, 1.0, 0.0) };
+    let right = helper.cross(&to_camera).normalize();
+    let up = to_camera.cross(&right);
+
+    let corner = |u: f32, v: f32| {
+        let position = center + right * ((u - 0.5) * 2.0 * half_size) + up * ((v - 0.5) * 2.0 * half_size);
+        Vertex::new(position, to_camera, Vec2::new(u, v))
+    };
+    let (a, b, c, d) = (corner(0.0, 0.0), corner(1.0, 0.0), corner(1.0, 1.0), corner(0.0, 1.0));
+    vec![a.clone(), b, c.clone(), a, c, d]
+}
+
+// Cinta orientada hacia la cámara a lo largo de `points`. UV: u a lo ancho, v a lo largo
+fn ribbon(points: &[Vec3], half_widths: &[f32], camera_position: Vec3) -> Vec<Vertex> {
+    let mut vertices = Vec::with_capacity(points.len() * 6);
+    if points.len() < 2 {
+        return vertices;
+    }
+
+    let edge = |i: usize| {
+        let tangent = if i + 1 < points.len() { points[i + 1] - points[i] } else { points[i] - points[i - 1] };
+        let to_camera = camera_position - points[i];
+        let side = tangent.cross(&to_camera);
+        let side = if side.magnitude() > 1e-6 { side.normalize() } else { Vec3::zeros() };
+        let v = i as f32 / (points.len() - 1) as f32;
+        let normal = to_camera.normalize();
+        (
+            Vertex::new(points[i] - side * half_widths[i], normal, Vec2::new(0.0, v)),
+            Vertex::new(points[i] + side * half_widths[i], normal, Vec2::new(1.0, v)),
+        )
+    };
+
+    let mut previous = edge(0);
+    for i in 1..points.len() {
+        let current = edge(i);
+        vertices.extend([
+            previous.0.clone(), previous.1.clone(), current.1.clone(),
+            previous.0.clone(), current.1.clone(), current.0.clone(),
+        ]);
+        previous = current;
+    }
+    vertices
+}
+
+/// Brillo de la coma: perfil gaussiano alrededor del núcleo
+pub fn coma_shader(fragment: &Fragment, uniforms: &Uniforms) -> FragmentOutput {
+    let Some(comet) = &uniforms.comet else {
+        return FragmentOutput::surface(Color::black());
+    };
+
+    let offset = (fragment.tex_coords - Vec2::new(0.5, 0.5)) * 2.0;
+    let glow = (-offset.magnitude_squared() * 5.0).exp() * smoothstep(1.0, 0.8, offset.magnitude());
+    let intensity = glow * (0.5 + 1.0 * comet.activity.sqrt().min(1.0));
+
+    FragmentOutput::new(Color::black(), comet.settings.coma_color * intensity)
+}
+
+/// Cola de iones: estrecha, con estrías que se alejan de la estrella
+pub fn ion_tail_shader(fragment: &Fragment, uniforms: &Uniforms) -> FragmentOutput {
+    let Some(comet) = &uniforms.comet else {
+        return FragmentOutput::surface(Color::black());
+    };
+
+    let across = (fragment.tex_coords.x - 0.5) * 2.0;
+    let along = fragment.tex_coords.y;
+    let streamers = uniforms.noise.get_noise_2d(across * 40.0, along * 30.0 - uniforms.time * 40.0);
+    let profile = (-(across * across) * 6.0).exp() * (0.7 + 0.3 * streamers);
+    let intensity = profile * (1.0 - along).powf(1.5) * 1.6 * comet.activity.sqrt().min(1.0);
+
+    FragmentOutput::new(Color::black(), comet.settings.ion_color * intensity)
+}
+
+/// Cola de polvo: ancha, difusa y curvada
+pub fn dust_tail_shader(fragment: &Fragment, uniforms: &Uniforms) -> FragmentOutput {
+    let Some(comet) = &uniforms.comet else {
+        return FragmentOutput::surface(Color::black());
+    };
+
+    let across = (fragment.tex_coords.x - 0.5) * 2.0;
+    let along = fragment.tex_coords.y;
+    let profile = (-(across * across) * 2.5).exp();
+    let intensity = profile * (1.0 - along).powi(2) * 1.3 * comet.activity.sqrt().min(1.0);
+
+    FragmentOutput::new(Color::black(), comet.settings.dust_color * intensity)
+}
@@ -0,0 +1,302 @@
+// src/controls.rs
+
+use nalgebra_glm::Vec3;
+use minifb::{Key, MouseButton, MouseMode, Window};
+use crate::camera::Camera;
+use crate::projection::ProjectionKind;
+
+/// Órbita alrededor del cuerpo seleccionado: flechas o arrastrar con el ratón para girar,
+/// Q/E para rotar la vista y Z/X para acercarse o alejarse
+pub struct OrbitController {
+    pub rotation_speed: f32,
+    pub zoom_factor: f32,
+    pub min_distance: f32,
+    last_mouse: Option<(f32, f32)>,
+}
+
+/// Vuelo libre: WASD para moverse, Espacio/Mayús izquierda para subir y bajar, Q/E para
+/// rotar la vista y arrastrar con el ratón para mirar
+pub struct FreeFlyController {
+    pub move_speed: f32,       // Unidades por segundo
+    pub look_sensitivity: f32, // Radianes por píxel
+    last_mouse: Option<(f32, f32)>,
+}
+
+/// Sigue a un cuerpo en movimiento manteniendo el desfase, que cambia con un retraso suave.
+/// Las flechas giran el desfase, Q/E rotan la vista y Z/X acercan o alejan
+pub struct FollowController {
+    pub offset: Vec3,
+    pub rotation_speed: f32,
+    pub stiffness: f32, // Mayor = la cámara alcanza antes su posición
+    smoothed_offset: Vec3,
+}
+
+/// Controlador activo de la cámara. Cada uno conserva su estado entre cuadros
+pub enum CameraController {
+    Orbit(OrbitController),
+    FreeFly(FreeFlyController),
+    Follow(FollowController),
+}
+
+impl CameraController {
+    pub fn orbit() -> Self {
+        CameraController::Orbit(OrbitController { rotation_speed: 0.05, zoom_factor: 1.1, min_distance: 1.0, last_mouse: None })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CameraController::Orbit(_) => "Órbita",
+            CameraController::FreeFly(_) => "Vuelo libre",
+            CameraController::Follow(_) => "Seguimiento",
+        }
+    }
+
+    /// Pasa al siguiente controlador, arrancando desde el estado actual de la cámara
+    pub fn next(&self, camera: &Camera, target: Vec3) -> Self {
+        match self {
+            CameraController::Orbit(_) => CameraController::FreeFly(FreeFlyController {
+                move_speed: 4.0,
+                look_sensitivity: 0.004,
+                last_mouse: None,
+            }),
+            CameraController::FreeFly(_) => CameraController::Follow(FollowController {
+                offset: camera.eye() - target,
+                rotation_speed: 0.05,
+                stiffness: 4.0,
+                smoothed_offset: camera.eye() - target,
+            }),
+            CameraController::Follow(_) => CameraController::orbit(),
+        }
+    }
+
+    /// Ajusta el estado propio del controlador a la cámara tras moverla desde fuera
+    /// (p. ej. al terminar una transición), para que no salte en el siguiente cuadro
+    pub fn sync(&mut self, camera: &Camera, target: Vec3) {
+        match self {
+            CameraController::Orbit(orbit) => orbit.last_mouse = None,
+            CameraController::FreeFly(fly) => fly.last_mouse = None,
+            CameraController::Follow(follow) => {
+                follow.offset = camera.eye() - target;
+                follow.smoothed_offset = follow.offset;
+            }
+        }
+    }
+
+    /// Actualiza la cámara con la entrada del cuadro. `target` es el cuerpo a enfocar
+    /// (el seleccionado en órbita, el que se sigue en seguimiento)
+    pub fn update(&mut self, camera: &mut Camera, window: &Window, target: Vec3, dt: f32) {
+        match self {
+            CameraController::Orbit(orbit) => {
+                // El centro sigue al cuerpo sin cambiar el desfase de la cámara
+                camera.set_center(target);
+
+                let (yaw, pitch) = arrow_keys(window, orbit.rotation_speed);
+                if yaw != 0.0 || pitch != 0.0 {
+                    camera.orbit(yaw, pitch);
+                }
+                roll_keys(camera, window, Key::Q, Key::E, orbit.rotation_speed);
+
+                // Trackball con el botón izquierdo
+                let mouse = window.get_mouse_pos(MouseMode::Pass).map(|position| normalized_mouse(window, position));
+                if window.get_mouse_down(MouseButton::Left) {
+                    if let (Some(to), Some(from)) = (mouse, orbit.last_mouse) {
+                        camera.trackball(from, to);
+                    }
+                }
+                orbit.last_mouse = mouse;
+
+                let distance = (camera.eye() - camera.center()).magnitude();
+                if window.is_key_down(Key::Z) {
+                    let closer = (distance / orbit.zoom_factor).max(orbit.min_distance);
+                    camera.zoom(distance - closer);
+                }
+                if window.is_key_down(Key::X) {
+                    camera.zoom(distance - distance * orbit.zoom_factor);
+                }
+            }
+            CameraController::FreeFly(fly) => {
+                // Dirección local: x a la derecha, y hacia arriba, -z hacia delante
+                let mut local = Vec3::zeros();
+                let bindings = [
+                    (Key::W, Vec3::new(0.0, 0.0, -1.0)),
+                    (Key::S, Vec3::new(0.0, 0.0, 1.0)),
+                    (Key::A, Vec3::new(-1.0, 0.0, 0.0)),
+                    (Key::D, Vec3::new(1.0, 0.0, 0.0)),
+                    (Key::Space, Vec3::new(0.0, 1.0, 0.0)),
+                    (Key::LeftShift, Vec3::new(0.0, -1.0, 0.0)),
+                ];
+                for (key, direction) in bindings {
+                    if window.is_key_down(key) {
+                        local += direction;
+                    }
+                }
+                if local.magnitude() > 0.0 {
+                    camera.translate(camera.basis_change(&local) * fly.move_speed * dt);
+                }
+                roll_keys(camera, window, Key::Q, Key::E, 0.03);
+
+                // Mirar alrededor arrastrando con el botón izquierdo
+                let mouse = window.get_mouse_pos(MouseMode::Pass);
+                if window.get_mouse_down(MouseButton::Left) {
+                    if let (Some((x, y)), Some((last_x, last_y))) = (mouse, fly.last_mouse) {
+                        let delta = Vec3::new(last_x - x, last_y - y, 0.0) * (fly.look_sensitivity / 0.05);
+                        camera.move_center(delta);
+                    }
+                }
+                fly.last_mouse = mouse;
+
+                let (yaw, pitch) = arrow_keys(window, 0.05);
+                if yaw != 0.0 || pitch != 0.0 {
+                    camera.move_center(Vec3::new(-yaw, pitch, 0.0) / 0.05 * 0.03);
+                }
+            }
+            CameraController::Follow(follow) => {
+                // Se gira el desfase con las flechas y se acerca con Z/X
+                let (yaw, pitch) = arrow_keys(window, follow.rotation_speed);
+                let mut rig = Camera::new(target + follow.offset, target, camera.up());
+                if yaw != 0.0 || pitch != 0.0 {
+                    rig.orbit(yaw, pitch);
+                }
+                roll_keys(&mut rig, window, Key::Q, Key::E, follow.rotation_speed);
+                let distance = follow.offset.magnitude();
+                if window.is_key_down(Key::Z) {
+                    rig.zoom(distance - distance / 1.1);
+                }
+                if window.is_key_down(Key::X) {
+                    rig.zoom(distance - distance * 1.1);
+                }
+                follow.offset = rig.eye() - target;
+
+                // Suavizado exponencial del desfase, independiente de la tasa de cuadros. Se
+                // suaviza respecto al cuerpo y no en el mundo para no perder de vista a los
+                // rápidos (el cometa recorre millones de unidades por segundo)
+                let blend = 1.0 - (-follow.stiffness * dt).exp();
+                follow.smoothed_offset += (follow.offset - follow.smoothed_offset) * blend;
+                camera.set_look_at(target + follow.smoothed_offset, target, rig.up());
+            }
+        }
+    }
+}
+
+/// Transición animada de la cámara: interpola el centro, la dirección de vista y la
+/// distancia (en escala logarítmica, como un zoom) con una curva suave
+pub struct CameraTransition {
+    from_center: Vec3,
+    to_center: Vec3,
+    from_direction: Vec3, // Del centro hacia el ojo
+    to_direction: Vec3,
+    from_distance: f32,
+    to_distance: f32,
+    from_half_height: f32, // Media altura visible, solo se aplica en ortográfica
+    to_half_height: f32,
+    elapsed: f32,
+    pub duration: f32,
+}
+
+impl CameraTransition {
+    pub fn new(camera: &Camera, to_eye: Vec3, to_center: Vec3, duration: f32) -> Self {
+        let from_offset = camera.eye() - camera.center();
+        let to_offset = to_eye - to_center;
+        CameraTransition {
+            from_center: camera.center(),
+            to_center,
+            from_direction: from_offset.normalize(),
+            to_direction: to_offset.normalize(),
+            from_distance: from_offset.magnitude(),
+            to_distance: to_offset.magnitude(),
+            from_half_height: camera.projection.half_height,
+            to_half_height: camera.projection.half_height,
+            elapsed: 0.0,
+            duration,
+        }
+    }
+
+    /// Vuela hasta `target` y lo encuadra: se conserva la dirección de vista actual y la
+    /// distancia se elige para que una esfera de radio `radius` llene el campo de visión `fov`.
+    /// En ortográfica la distancia no cambia el tamaño, así que se ajusta la altura visible
+    pub fn fly_to(camera: &Camera, target: Vec3, radius: f32, fov: f32, duration: f32) -> Self {
+        let margin = 1.3; // Espacio libre alrededor del cuerpo
+        let distance = radius * margin / (fov * 0.5).sin();
+        let direction = (camera.eye() - camera.center()).normalize();
+        let mut transition = CameraTransition::new(camera, target + direction * distance, target, duration);
+        transition.to_half_height = radius * margin;
+        transition
+    }
+
+    /// Cambia el centro de destino; se llama en cada cuadro con la posición local del
+    /// cuerpo, recalculada en f64, para no acumular el error de los desplazamientos
+    pub fn retarget(&mut self, center: Vec3) {
+        self.to_center = center;
+    }
+
+    /// Desplaza los centros de la transición (al cambiar el origen flotante)
+    pub fn shift(&mut self, offset: Vec3) {
+        self.from_center += offset;
+        self.to_center += offset;
+    }
+
+    /// Avanza la transición y coloca la cámara. Devuelve `false` cuando ha terminado
+    pub fn update(&mut self, camera: &mut Camera, dt: f32) -> bool {
+        self.elapsed = (self.elapsed + dt).min(self.duration);
+        let t = ease_in_out(if self.duration > 0.0 { self.elapsed / self.duration } else { 1.0 });
+
+        let center = self.from_center.lerp(&self.to_center, t);
+        // Dirección normalizada tras interpolar; si son opuestas se pasa por el eje vertical
+        let mut direction = self.from_direction.lerp(&self.to_direction, t);
+        if direction.magnitude() < 1e-3 {
+            direction = camera.up();
+        }
+        let distance = self.from_distance * (self.to_distance / self.from_distance).powf(t);
+
+        let up = camera.up();
+        camera.set_look_at(center + direction.normalize() * distance, center, up);
+        if camera.projection.kind == ProjectionKind::Orthographic {
+            camera.projection.half_height = self.from_half_height * (self.to_half_height / self.from_half_height).powf(t);
+        }
+        self.elapsed < self.duration
+    }
+}
+
+// Curva cúbica que acelera al principio y frena al final
+fn ease_in_out(t: f32) -> f32 {
+    if t < 0.5 {
+        4.0 * t * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+    }
+}
+
+// Rota la vista mientras se mantienen pulsadas las teclas indicadas
+fn roll_keys(camera: &mut Camera, window: &Window, left: Key, right: Key, speed: f32) {
+    if window.is_key_down(left) {
+        camera.roll(speed);
+    }
+    if window.is_key_down(right) {
+        camera.roll(-speed);
+    }
+}
+
+// Posición del ratón en coordenadas normalizadas [-1, 1], con y hacia arriba
+fn normalized_mouse(window: &Window, (x, y): (f32, f32)) -> (f32, f32) {
+    let (width, height) = window.get_size();
+    (2.0 * x / width as f32 - 1.0, 1.0 - 2.0 * y / height as f32)
+}
+
+// Giro (yaw, pitch) pedido con las flechas en este cuadro
+fn arrow_keys(window: &Window, speed: f32) -> (f32, f32) {
+    let mut yaw = 0.0;
+    let mut pitch = 0.0;
+    if window.is_key_down(Key::Up) {
+        pitch += speed;
+    }
+    if window.is_key_down(Key::Down) {
+        pitch -= speed;
+    }
+    if window.is_key_down(Key::Left) {
+        yaw -= speed;
+    }
+    if window.is_key_down(Key::Right) {
+        yaw += speed;
+    }
+    (yaw, pitch)
+}
@@ -0,0 +1,191 @@
+// src/craters.rs
+
+use nalgebra_glm::Vec3;
+use fastnoise_lite::FastNoiseLite;
+use crate::fragment::Fragment;
+use crate::random::Rng;
+use crate::shaders::smoothstep;
+use crate::vertex::Vertex;
+use crate::Uniforms;
+
+const EJECTA_REACH: f32 = 2.5; // Alcance del manto de eyecta, en radios del cráter
+const GRID_SIZE: usize = 8;     // Celdas por eje de la rejilla de búsqueda
+
+/// Cráter de impacto sobre la esfera unitaria. Los radios son cuerdas sobre la esfera
+pub struct Crater {
+    pub center: Vec3,
+    pub radius: f32,
+    pub depth: f32,       // Profundidad del cuenco
+    pub rim_height: f32,
+    pub rays: bool,       // Cráteres jóvenes con rayos de eyecta brillantes
+    tangent: Vec3,        // Base local para el ángulo alrededor del centro
+    bitangent: Vec3,
+}
+
+/// Mar: llanura de lava oscura y lisa
+pub struct Mare {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+/// Resultado de evaluar el campo en un punto
+#[derive(Clone, Copy, Default)]
+pub struct CraterSample {
+    pub height: f32,  // Altura relativa al radio del cuerpo
+    pub ejecta: f32,  // Material claro alrededor de los cráteres, en [0, 1]
+    pub floor: f32,   // Cuánto del punto está dentro de un cuenco, en [0, 1]
+    pub mare: f32,    // Cobertura de mar, en [0, 1]
+}
+
+/// Campo de cráteres y mares sobre una esfera, con una rejilla 3D para que cada
+/// consulta solo recorra los cráteres cercanos
+pub struct CraterField {
+    pub craters: Vec<Crater>,
+    pub maria: Vec<Mare>,
+    grid: Vec<Vec<usize>>,
+    grid_size: usize,
+}
+
+impl CraterField {
+    /// `count` cráteres con radios entre `min_radius` y `max_radius` siguiendo una ley de
+    /// potencias (muchos pequeños, pocos grandes), y `mare_count` mares
+    pub fn generate(seed: u64, count: usize, min_radius: f32, max_radius: f32, mare_count: usize) -> Self {
+        let mut rng = Rng::new(seed);
+
+        let maria: Vec<Mare> = (0..mare_count)
+            .map(|_| Mare { center: rng.unit_vector(), radius: rng.range(0.25, 0.5) })
+            .collect();
+
+        let craters = (0..count)
+            .map(|_| {
+                let center = rng.unit_vector();
+                // Distribución acumulada N(>r) ∝ r^-2
+                let t = rng.next_f32();
+                let radius = min_radius / (1.0 - t * (1.0 - (min_radius / max_radius).powi(2))).sqrt();
+                let helper = if center.y.abs() > 0.9 { Vec3::new(1.0, 0.0, 0.0) } else { Vec3::new(0.0, 1.0, 0.0) };
+                let tangent = helper.cross(&center).normalize();
+                Crater {
+                    center,
+                    radius,
+                    depth: radius * rng.range(0.15, 0.25),
+                    rim_height: radius * rng.range(0.04, 0.07),
+                    rays: radius > max_radius * 0.4 && rng.next_f32() < 0.3,
+                    tangent,
+                    bitangent: center.cross(&tangent),
+                }
+            })
+            .collect();
+
+        let mut field = CraterField { craters, maria, grid: Vec::new(), grid_size: GRID_SIZE };
+        field.build_grid();
+        field
+    }
+
+    // Reparte los cráteres en celdas de la rejilla según su zona de influencia
+    fn build_grid(&mut self) {
+        let size = self.grid_size;
+        let mut grid = vec![Vec::new(); size * size * size];
+
+        for (index, crater) in self.craters.iter().enumerate() {
+            let reach = crater.radius * EJECTA_REACH;
+            let low = (crater.center - Vec3::repeat(reach)).map(|v| grid_cell(v, size));
+            let high = (crater.center + Vec3::repeat(reach)).map(|v| grid_cell(v, size));
+            for x in low.x..=high.x {
+                for y in low.y..=high.y {
+                    for z in low.z..=high.z {
+                        grid[(x * size + y) * size + z].push(index);
+                    }
+                }
+            }
+        }
+
+        self.grid = grid;
+    }
+
+    /// Evalúa el campo en una dirección desde el centro del cuerpo. `noise` ondula los
+    /// bordes de los mares
+    pub fn sample(&self, direction: &Vec3, noise: &FastNoiseLite) -> CraterSample {
+        let p = direction.normalize();
+
+        let wobble = noise.get_noise_3d(p.x * 30.0, p.y * 30.0, p.z * 30.0) * 0.08;
+        let mare = self
+            .maria
+            .iter()
+            .map(|mare| smoothstep(mare.radius, mare.radius * 0.75, (p - mare.center).magnitude() + wobble))
+            .fold(0.0, f32::max);
+
+        let mut sample = CraterSample { mare, ..Default::default() };
+        let size = self.grid_size;
+        let cell = (grid_cell(p.x, size) * size + grid_cell(p.y, size)) * size + grid_cell(p.z, size);
+        for &index in &self.grid[cell] {
+            let crater = &self.craters[index];
+            let d = (p - crater.center).magnitude() / crater.radius;
+            if d >= EJECTA_REACH {
+                continue;
+            }
+
+            // Perfil: cuenco parabólico, borde elevado y manto de eyecta que decae hacia fuera
+            let bowl = if d < 1.0 { crater.depth * (d * d - 1.0) } else { 0.0 };
+            let rim = crater.rim_height * (-((d - 1.0) / 0.2).powi(2)).exp();
+            let blanket = if d > 1.0 { crater.rim_height * 0.3 * (1.0 - (d - 1.0) / (EJECTA_REACH - 1.0)).powi(2) } else { 0.0 };
+            sample.height += bowl + rim + blanket;
+            sample.floor = sample.floor.max(smoothstep(1.0, 0.7, d));
+
+            // Eyecta clara; los cráteres con rayos la extienden en franjas radiales
+            let mut ejecta = smoothstep(EJECTA_REACH * 0.6, 1.0, d) * 0.6;
+            if crater.rays && d > 1.0 {
+                let offset = p - crater.center;
+                let angle = offset.dot(&crater.bitangent).atan2(offset.dot(&crater.tangent));
+                let streaks = noise.get_noise_2d(angle.cos() * 7.0 + index as f32 * 13.0, angle.sin() * 7.0);
+                let streaks = (streaks + 1.0) * 0.5;
+                ejecta = ejecta.max(smoothstep(0.6, 0.9, streaks) * (1.0 - (d - 1.0) / (EJECTA_REACH - 1.0)));
+            }
+            sample.ejecta = sample.ejecta.max(ejecta);
+        }
+
+        // La lava de los mares cubrió los cráteres antiguos
+        sample.height *= 1.0 - 0.6 * mare;
+        sample
+    }
+
+    /// Normal perturbada por el relieve de los cráteres (espacio del cuerpo), por diferencias finitas.
+    /// `strength` exagera las pendientes
+    pub fn normal(&self, direction: &Vec3, noise: &FastNoiseLite, strength: f32) -> Vec3 {
+        let p = direction.normalize();
+        let helper = if p.y.abs() > 0.9 { Vec3::new(1.0, 0.0, 0.0) } else { Vec3::new(0.0, 1.0, 0.0) };
+        let tangent = helper.cross(&p).normalize();
+        let bitangent = p.cross(&tangent);
+
+        let epsilon = 0.003;
+        let height = self.sample(&p, noise).height;
+        let slope_t = (self.sample(&(p + tangent * epsilon), noise).height - height) / epsilon;
+        let slope_b = (self.sample(&(p + bitangent * epsilon), noise).height - height) / epsilon;
+        (p - (tangent * slope_t + bitangent * slope_b) * strength).normalize()
+    }
+
+    /// Desplaza los vértices de una malla a lo largo de la dirección radial según la altura
+    /// de los cráteres, en unidades del radio de cada vértice
+    pub fn displace(&self, vertices: &mut [Vertex], noise: &FastNoiseLite, amount: f32) {
+        for vertex in vertices.iter_mut() {
+            let height = self.sample(&vertex.position, noise).height;
+            vertex.position *= 1.0 + height * amount;
+            vertex.normal = vertex.position.normalize();
+        }
+    }
+}
+
+/// Muestra del campo de cráteres de los uniforms en un fragmento y la normal perturbada
+/// en espacio del mundo. `None` si el cuerpo no tiene cráteres
+pub fn crater_surface(fragment: &Fragment, uniforms: &Uniforms, strength: f32) -> Option<(CraterSample, Vec3)> {
+    let field = uniforms.craters.as_ref()?;
+    let direction = fragment.vertex_position.xyz();
+    let sample = field.sample(&direction, &uniforms.noise);
+    let body_normal = field.normal(&direction, &uniforms.noise, strength);
+    let normal = (uniforms.model_matrix.fixed_view::<3, 3>(0, 0) * body_normal).normalize();
+    Some((sample, normal))
+}
+
+// Celda de la rejilla para una coordenada en [-1, 1]
+fn grid_cell(coordinate: f32, size: usize) -> usize {
+    (((coordinate + 1.0) * 0.5 * size as f32).max(0.0) as usize).min(size - 1)
+}
@@ -4,27 +4,30 @@ use crate::color::Color;
 #[derive(Clone, Copy)]
 pub enum CelestialType {
     Star,
-    Planet,
+    Rocky, // Planeta rocoso: el terreno lo da `uniforms.terrain`
     GasGiant,
     Ringed,
     Rings,
-    Planet2,
-    Mars,
     Moon,
     Comet,
     Atmosphere, 
     // Para efectos atmosféricos
+    Corona,
+    Prominence,
+    Coma,
+    IonTail,
+    DustTail,
+    Asteroid,
     // Agrega otros tipos según sea necesario
 }
 
 
 pub struct Fragment {
     pub position: Vec2,
-    pub color: Color,
     pub depth: f32,
     pub normal: Vec3,
-    pub intensity: f32,
     pub vertex_position: Vec4, // Cambiado a Vec4
+    pub tex_coords: Vec2,
     pub celestial_type: CelestialType,
 }
 
@@ -32,21 +35,51 @@ impl Fragment {
     pub fn new(
         x: f32,
         y: f32,
-        color: Color,
         depth: f32,
         normal: Vec3,
-        intensity: f32,
         vertex_position: Vec4, // Ahora es Vec4
+        tex_coords: Vec2,
         celestial_type: CelestialType,
     ) -> Self {
         Fragment {
             position: Vec2::new(x, y),
-            color,
             depth,
             normal,
-            intensity,
             vertex_position,
+            tex_coords,
             celestial_type,
         }
     }
 }
+
+/// Resultado de un fragment shader: color de superficie y emisión.
+///
+/// La emisión se escribe en `Framebuffer::emissive_buffer` y se suma al color
+/// final durante el post-procesamiento (brillo).
+#[derive(Clone, Copy)]
+pub struct FragmentOutput {
+    pub color: Color,
+    pub emission: Color,
+    pub alpha: f32, // Opacidad, solo se usa con mezcla alfa
+}
+
+impl FragmentOutput {
+    pub fn new(color: Color, emission: Color) -> Self {
+        FragmentOutput { color, emission, alpha: 1.0 }
+    }
+
+    pub fn with_alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Superficie sin emisión propia
+    pub fn surface(color: Color) -> Self {
+        FragmentOutput::new(color, Color::black())
+    }
+
+    /// Superficie que emite su propio color (estrellas)
+    pub fn emissive(color: Color) -> Self {
+        FragmentOutput::new(color, color)
+    }
+}
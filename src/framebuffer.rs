@@ -1,11 +1,18 @@
+use crate::color::Color;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
 pub struct Framebuffer {
     pub width: usize,
     pub height: usize,
     pub buffer: Vec<u32>,
     pub zbuffer: Vec<f32>,
     pub emissive_buffer: Vec<u32>, // Nuevo buffer para emisivos
+    pub object_buffer: Vec<u32>, // Identificador del objeto visible en cada píxel (0 = ninguno)
+    pub reverse_z: bool, // Profundidad invertida: mayor = más cerca (ver `Projection`)
     background_color: u32,
     current_color: u32,
+    current_object: u32,
 }
 
 impl Framebuffer {
@@ -16,8 +23,11 @@ impl Framebuffer {
             buffer: vec![0; width * height],
             zbuffer: vec![f32::INFINITY; width * height],
             emissive_buffer: vec![0; width * height], // Inicializar
+            object_buffer: vec![0; width * height],
+            reverse_z: false,
             background_color: 0x000000,
             current_color: 0x000000,
+            current_object: 0,
         }
     }
 
@@ -25,28 +35,89 @@ impl Framebuffer {
         for pixel in self.buffer.iter_mut() {
             *pixel = self.background_color;
         }
+        let empty = if self.reverse_z { f32::NEG_INFINITY } else { f32::INFINITY };
         for depth in self.zbuffer.iter_mut() {
-            *depth = f32::INFINITY;
+            *depth = empty;
         }
         for emissive_pixel in self.emissive_buffer.iter_mut() {
             *emissive_pixel = 0x000000;
         }
+        for object in self.object_buffer.iter_mut() {
+            *object = 0;
+        }
+    }
+
+    // Indica si `depth` queda por delante de lo que ya hay en el píxel
+    fn depth_test(&self, index: usize, depth: f32) -> bool {
+        if self.reverse_z {
+            depth > self.zbuffer[index]
+        } else {
+            depth < self.zbuffer[index]
+        }
     }
 
-    pub fn point(&mut self, x: usize, y: usize, depth: f32, emissive: bool) {
+    pub fn point(&mut self, x: usize, y: usize, depth: f32, emission: u32) {
         if x < self.width && y < self.height {
             let index = y * self.width + x;
 
-            if self.zbuffer[index] > depth {
+            if self.depth_test(index, depth) {
                 self.buffer[index] = self.current_color;
-                if emissive {
-                    self.emissive_buffer[index] = self.current_color;
-                }
+                // Se escribe siempre para que una superficie opaca tape la emisión detrás
+                self.emissive_buffer[index] = emission;
                 self.zbuffer[index] = depth;
+                self.object_buffer[index] = self.current_object;
             }
         }
     }
 
+    /// Mezcla aditiva con prueba de profundidad, sin escribir en el z-buffer
+    /// (capas translúcidas como la atmósfera)
+    pub fn point_additive(&mut self, x: usize, y: usize, depth: f32, emission: u32) {
+        if x < self.width && y < self.height {
+            let index = y * self.width + x;
+
+            if self.depth_test(index, depth) {
+                self.buffer[index] = Color::from_hex(self.buffer[index])
+                    .blend_add(&Color::from_hex(self.current_color))
+                    .to_hex();
+                self.emissive_buffer[index] = Color::from_hex(self.emissive_buffer[index])
+                    .blend_add(&Color::from_hex(emission))
+                    .to_hex();
+            }
+        }
+    }
+
+    /// Mezcla alfa con prueba de profundidad, sin escribir en el z-buffer
+    /// (capas semitransparentes como los anillos)
+    pub fn point_alpha(&mut self, x: usize, y: usize, depth: f32, emission: u32, alpha: f32) {
+        if x < self.width && y < self.height && alpha > 0.0 {
+            let index = y * self.width + x;
+
+            if self.depth_test(index, depth) {
+                self.buffer[index] = Color::from_hex(self.buffer[index])
+                    .lerp(&Color::from_hex(self.current_color), alpha)
+                    .to_hex();
+                self.emissive_buffer[index] = Color::from_hex(self.emissive_buffer[index])
+                    .lerp(&Color::from_hex(emission), alpha)
+                    .to_hex();
+                // Solo las partes casi opacas cuentan para la selección
+                if alpha >= 0.5 {
+                    self.object_buffer[index] = self.current_object;
+                }
+            }
+        }
+    }
+
+    /// Guarda el buffer de color como imagen PPM (binaria)
+    pub fn save_ppm(&self, filename: &str) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(filename)?);
+        write!(file, "P6\n{} {}\n255\n", self.width, self.height)?;
+        for &pixel in &self.buffer {
+            file.write_all(&[(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8])?;
+        }
+        file.flush()
+    }
+
     pub fn set_background_color(&mut self, color: u32) {
         self.background_color = color;
     }
@@ -54,4 +125,18 @@ impl Framebuffer {
     pub fn set_current_color(&mut self, color: u32) {
         self.current_color = color;
     }
+
+    /// Identificador que se escribe en el buffer de objetos con los puntos siguientes
+    pub fn set_current_object(&mut self, object: u32) {
+        self.current_object = object;
+    }
+
+    /// Objeto visible en un píxel, si hay alguno
+    pub fn object_at(&self, x: usize, y: usize) -> Option<u32> {
+        if x < self.width && y < self.height {
+            Some(self.object_buffer[y * self.width + x]).filter(|&object| object != 0)
+        } else {
+            None
+        }
+    }
 }
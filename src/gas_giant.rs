@@ -0,0 +1,197 @@
+// src/gas_giant.rs
+
+use nalgebra_glm::Vec3;
+use crate::color::Color;
+use crate::fragment::{Fragment, FragmentOutput};
+use crate::atmosphere::{self, AtmosphereSettings};
+use crate::rings::ring_shadow;
+use crate::shaders::{apply_lighting_with_shadow, body_light_direction, smoothstep};
+use crate::Uniforms;
+
+/// Banda de nubes entre la banda anterior y `top` (latitud en grados)
+pub struct Band {
+    pub top: f32,
+    pub color: Color,
+    pub wind_speed: f32, // Velocidad angular de la corriente zonal (rad/s); > 0 hacia el este
+}
+
+/// Vórtice elíptico persistente (como la Gran Mancha Roja)
+pub struct Vortex {
+    pub latitude: f32,   // Grados
+    pub longitude: f32,  // Grados, en t = 0
+    pub width: f32,      // Semieje en longitud, grados
+    pub height: f32,     // Semieje en latitud, grados
+    pub drift: f32,      // Deriva en longitud (rad/s)
+    pub spin: f32,       // Giro de las espirales internas; el signo da el sentido
+    pub color: Color,
+}
+
+/// Modelo de un gigante gaseoso: bandas con vientos propios, bordes turbulentos y vórtices
+pub struct GasGiantPreset {
+    pub bands: &'static [Band],   // Ordenadas de sur a norte
+    pub edge_width: f32,          // Anchura de la transición entre bandas (grados)
+    pub turbulence_scale: f32,    // Escala de las coordenadas para el ruido FBm del cuerpo
+    pub warp_strength: f32,       // Intensidad de la deformación del dominio
+    pub edge_turbulence: f32,     // Cuánto ondulan los bordes de las bandas (grados)
+    pub streak_contrast: f32,     // Contraste de las vetas dentro de cada banda
+    pub vortices: &'static [Vortex],
+    pub atmosphere: AtmosphereSettings,
+}
+
+pub const JUPITER: GasGiantPreset = GasGiantPreset {
+    bands: &[
+        Band { top: -60.0, color: Color::new(125, 112, 100), wind_speed: 0.0 },
+        Band { top: -35.0, color: Color::new(180, 155, 125), wind_speed: 0.02 },
+        Band { top: -18.0, color: Color::new(220, 200, 165), wind_speed: -0.02 },
+        Band { top: -7.0, color: Color::new(160, 105, 65), wind_speed: 0.05 },
+        Band { top: 7.0, color: Color::new(230, 212, 180), wind_speed: 0.12 },
+        Band { top: 18.0, color: Color::new(150, 95, 60), wind_speed: 0.04 },
+        Band { top: 35.0, color: Color::new(215, 195, 160), wind_speed: -0.02 },
+        Band { top: 60.0, color: Color::new(175, 145, 115), wind_speed: 0.02 },
+        Band { top: 90.0, color: Color::new(125, 112, 100), wind_speed: 0.0 },
+    ],
+    edge_width: 3.0,
+    turbulence_scale: 200.0,
+    warp_strength: 0.8,
+    edge_turbulence: 7.0,
+    streak_contrast: 0.25,
+    vortices: &[
+        // Gran Mancha Roja
+        Vortex { latitude: -22.0, longitude: 80.0, width: 14.0, height: 6.5, drift: -0.015, spin: 3.0, color: Color::new(195, 90, 55) },
+        // Óvalos blancos
+        Vortex { latitude: -33.0, longitude: 130.0, width: 5.0, height: 2.5, drift: 0.01, spin: -2.0, color: Color::new(238, 232, 220) },
+        Vortex { latitude: -33.0, longitude: 200.0, width: 4.0, height: 2.0, drift: 0.01, spin: -2.0, color: Color::new(238, 232, 220) },
+    ],
+    atmosphere: atmosphere::GAS_GIANT_HAZE,
+};
+
+pub const SATURN: GasGiantPreset = GasGiantPreset {
+    bands: &[
+        Band { top: -55.0, color: Color::new(170, 160, 135), wind_speed: 0.0 },
+        Band { top: -30.0, color: Color::new(205, 185, 140), wind_speed: 0.02 },
+        Band { top: -12.0, color: Color::new(191, 150, 105), wind_speed: 0.04 },
+        Band { top: 12.0, color: Color::new(225, 205, 160), wind_speed: 0.15 },
+        Band { top: 30.0, color: Color::new(195, 155, 110), wind_speed: 0.04 },
+        Band { top: 55.0, color: Color::new(210, 190, 145), wind_speed: 0.02 },
+        Band { top: 90.0, color: Color::new(165, 160, 140), wind_speed: 0.0 },
+    ],
+    edge_width: 5.0,
+    turbulence_scale: 150.0,
+    warp_strength: 0.5,
+    edge_turbulence: 4.0,
+    streak_contrast: 0.1,
+    vortices: &[
+        // Tormenta blanca del hemisferio norte
+        Vortex { latitude: 40.0, longitude: -40.0, width: 6.0, height: 2.5, drift: 0.02, spin: -2.0, color: Color::new(235, 225, 200) },
+    ],
+    atmosphere: atmosphere::GAS_GIANT_HAZE,
+};
+
+impl GasGiantPreset {
+    /// Velocidad del viento zonal a una latitud, interpolada suavemente entre bandas
+    pub fn wind_speed(&self, latitude: f32) -> f32 {
+        self.blend_bands(latitude, |band| band.wind_speed)
+    }
+
+    /// Color de las bandas a una latitud, con transiciones suaves en los bordes
+    pub fn band_color(&self, latitude: f32) -> Color {
+        let mut color = self.bands[0].color;
+        let mut bottom = -90.0;
+        for band in self.bands {
+            let t = smoothstep(bottom - self.edge_width, bottom + self.edge_width, latitude);
+            color = color.lerp(&band.color, t);
+            bottom = band.top;
+        }
+        color
+    }
+
+    fn blend_bands(&self, latitude: f32, value: impl Fn(&Band) -> f32) -> f32 {
+        let mut result = value(&self.bands[0]);
+        let mut bottom = -90.0;
+        for band in self.bands {
+            let t = smoothstep(bottom - self.edge_width, bottom + self.edge_width, latitude);
+            result += (value(band) - result) * t;
+            bottom = band.top;
+        }
+        result
+    }
+
+    // FBm del cuerpo con el dominio deformado por otras tres muestras del mismo ruido
+    fn warped_noise(&self, uniforms: &Uniforms, point: &Vec3) -> f32 {
+        let p = point * self.turbulence_scale;
+        let warp = Vec3::new(
+            uniforms.noise.get_noise_3d(p.x + 31.7, p.y, p.z),
+            uniforms.noise.get_noise_3d(p.x, p.y + 57.3, p.z),
+            uniforms.noise.get_noise_3d(p.x, p.y, p.z + 11.1),
+        );
+        let q = p + warp * (self.warp_strength * self.turbulence_scale * 0.1);
+        uniforms.noise.get_noise_3d(q.x, q.y, q.z)
+    }
+
+    // Aporte de los vórtices en (latitud, longitud) en grados: (color, opacidad)
+    fn vortex(&self, uniforms: &Uniforms, latitude: f32, longitude: f32) -> Option<(Color, f32)> {
+        for vortex in self.vortices {
+            let center = vortex.longitude + (vortex.drift * uniforms.time).to_degrees();
+            let d_lon = (longitude - center + 180.0).rem_euclid(360.0) - 180.0;
+            let x = d_lon * latitude.to_radians().cos() / vortex.width;
+            let y = (latitude - vortex.latitude) / vortex.height;
+            let r = (x * x + y * y).sqrt();
+            if r >= 1.2 {
+                continue;
+            }
+
+            // Espirales: el ángulo gira más cerca del centro
+            let angle = y.atan2(x) + vortex.spin * (1.0 - r) + uniforms.time * vortex.spin * 0.05;
+            let swirl = uniforms.noise.get_noise_2d(
+                angle.cos() * r * self.turbulence_scale * 0.3,
+                angle.sin() * r * self.turbulence_scale * 0.3,
+            );
+            let core = vortex.color * (0.9 + 0.2 * swirl);
+
+            // Collar claro alrededor del óvalo
+            let collar = smoothstep(0.75, 1.0, r) * smoothstep(1.2, 1.0, r);
+            let color = core.lerp(&Color::new(235, 225, 205), collar * 0.6);
+            let opacity = smoothstep(1.2, 0.9, r);
+            return Some((color, opacity));
+        }
+        None
+    }
+}
+
+/// Shader de gigante gaseoso con rotación diferencial por latitud
+pub fn gas_giant_shader(fragment: &Fragment, uniforms: &Uniforms, preset: &GasGiantPreset) -> FragmentOutput {
+    let direction = fragment.vertex_position.xyz().normalize();
+    let latitude = direction.y.clamp(-1.0, 1.0).asin().to_degrees();
+
+    // Cada latitud gira con su propio viento: se desplaza la longitud de muestreo
+    let angle = uniforms.time * preset.wind_speed(latitude);
+    let (sin_a, cos_a) = angle.sin_cos();
+    let advected = Vec3::new(
+        direction.x * cos_a - direction.z * sin_a,
+        direction.y,
+        direction.x * sin_a + direction.z * cos_a,
+    );
+
+    // Bordes turbulentos: la latitud de la banda se ondula con ruido deformado
+    let turbulence = preset.warped_noise(uniforms, &advected);
+    let band_latitude = latitude + turbulence * preset.edge_turbulence;
+    let mut color = preset.band_color(band_latitude);
+
+    // Vetas estiradas en longitud dentro de cada banda
+    let streak = uniforms.noise.get_noise_3d(
+        advected.x * preset.turbulence_scale * 0.5,
+        advected.y * preset.turbulence_scale * 4.0,
+        advected.z * preset.turbulence_scale * 0.5,
+    );
+    color = color * (1.0 + preset.streak_contrast * (streak + turbulence * 0.5));
+
+    // Los vórtices derivan por su cuenta, no con el viento de su banda
+    let longitude = direction.z.atan2(direction.x).to_degrees();
+    if let Some((vortex_color, opacity)) = preset.vortex(uniforms, latitude, longitude) {
+        color = color.lerp(&vortex_color, opacity);
+    }
+
+    // Sombra de los anillos, si el cuerpo los tiene
+    let shadow = ring_shadow(uniforms, &fragment.vertex_position.xyz(), &body_light_direction(uniforms));
+    FragmentOutput::surface(apply_lighting_with_shadow(color, fragment, uniforms, shadow))
+}
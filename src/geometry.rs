@@ -0,0 +1,129 @@
+// src/geometry.rs
+
+use nalgebra_glm::{Vec2, Vec3};
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use fastnoise_lite::{FastNoiseLite, FractalType, NoiseType};
+use crate::random::Rng;
+use crate::vertex::Vertex;
+
+/// Genera una icosfera de radio 1 como lista de triángulos (3 vértices por cara),
+/// en el mismo formato que `Obj::get_vertex_array`
+pub fn icosphere(subdivisions: u32) -> Vec<Vertex> {
+    let t = (1.0 + 5.0_f32.sqrt()) / 2.0;
+
+    let mut positions: Vec<Vec3> = [
+        (-1.0, t, 0.0), (1.0, t, 0.0), (-1.0, -t, 0.0), (1.0, -t, 0.0),
+        (0.0, -1.0, t), (0.0, 1.0, t), (0.0, -1.0, -t), (0.0, 1.0, -t),
+        (t, 0.0, -1.0), (t, 0.0, 1.0), (-t, 0.0, -1.0), (-t, 0.0, 1.0),
+    ]
+    .iter()
+    .map(|&(x, y, z)| Vec3::new(x, y, z).normalize())
+    .collect();
+
+    let mut faces: Vec<[u32; 3]> = vec![
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+        let mut midpoint = |a: u32, b: u32, positions: &mut Vec<Vec3>| -> u32 {
+            let key = (a.min(b), a.max(b));
+            *midpoints.entry(key).or_insert_with(|| {
+                positions.push(((positions[a as usize] + positions[b as usize]) * 0.5).normalize());
+                (positions.len() - 1) as u32
+            })
+        };
+
+        let mut subdivided = Vec::with_capacity(faces.len() * 4);
+        for [a, b, c] in faces {
+            let ab = midpoint(a, b, &mut positions);
+            let bc = midpoint(b, c, &mut positions);
+            let ca = midpoint(c, a, &mut positions);
+            subdivided.extend_from_slice(&[[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]);
+        }
+        faces = subdivided;
+    }
+
+    faces
+        .iter()
+        .flat_map(|face| face.iter().map(|&index| sphere_vertex(positions[index as usize])))
+        .collect()
+}
+
+/// Genera un anillo plano en el plano XZ (normal +Y) entre `inner_radius` y `outer_radius`,
+/// dividido en `bands` franjas concéntricas para que u no se deforme sin corrección de perspectiva.
+/// UV: u = posición radial (0 en el borde interior), v = ángulo / 2π
+pub fn annulus(inner_radius: f32, outer_radius: f32, segments: u32, bands: u32) -> Vec<Vertex> {
+    let segments = segments.max(3);
+    let bands = bands.max(1);
+    let normal = Vec3::new(0.0, 1.0, 0.0);
+    let point = |band: u32, i: u32| {
+        let u = band as f32 / bands as f32;
+        let v = i as f32 / segments as f32;
+        let radius = inner_radius + (outer_radius - inner_radius) * u;
+        let (sin_a, cos_a) = (v * 2.0 * PI).sin_cos();
+        Vertex::new(Vec3::new(radius * cos_a, 0.0, radius * sin_a), normal, Vec2::new(u, v))
+    };
+
+    let mut vertices = Vec::with_capacity((segments * bands) as usize * 6);
+    for band in 0..bands {
+        for i in 0..segments {
+            let (inner_a, inner_b) = (point(band, i), point(band, i + 1));
+            let (outer_a, outer_b) = (point(band + 1, i), point(band + 1, i + 1));
+            vertices.extend([inner_a.clone(), outer_a, outer_b.clone(), inner_a, outer_b, inner_b]);
+        }
+    }
+    vertices
+}
+
+/// Roca irregular: icosfera estirada en sus ejes y deformada con ruido FBm.
+/// Las normales son por cara para que las aristas se vean facetadas
+pub fn rock(seed: u64, subdivisions: u32, roughness: f32) -> Vec<Vertex> {
+    let mut rng = Rng::new(seed);
+    let axes = Vec3::new(1.0, rng.range(0.6, 0.9), rng.range(0.45, 0.75));
+
+    let mut noise = FastNoiseLite::with_seed(seed as i32);
+    noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+    noise.set_frequency(Some(1.3));
+    noise.set_fractal_type(Some(FractalType::FBm));
+    noise.set_fractal_octaves(Some(3));
+
+    // El desplazamiento solo depende de la posición, así que los vértices compartidos coinciden
+    let displace = |position: Vec3| {
+        let height = 1.0 + roughness * noise.get_noise_3d(position.x, position.y, position.z);
+        position.component_mul(&axes) * height
+    };
+
+    let mut vertices = icosphere(subdivisions);
+    for face in vertices.chunks_mut(3) {
+        let (a, b, c) = (displace(face[0].position), displace(face[1].position), displace(face[2].position));
+        let mut normal = (b - a).cross(&(c - a)).normalize();
+        if normal.dot(&(a + b + c)) < 0.0 {
+            normal = -normal;
+        }
+        for (vertex, position) in face.iter_mut().zip([a, b, c]) {
+            vertex.position = position;
+            vertex.normal = normal;
+        }
+    }
+    vertices
+}
+
+/// Radio medio de una malla respecto a su origen (útil para mallas de esferas irregulares)
+pub fn mean_radius(vertices: &[Vertex]) -> f32 {
+    if vertices.is_empty() {
+        return 0.0;
+    }
+    vertices.iter().map(|v| v.position.magnitude()).sum::<f32>() / vertices.len() as f32
+}
+
+// Vértice sobre la esfera unitaria con normal radial y coordenadas UV esféricas
+fn sphere_vertex(position: Vec3) -> Vertex {
+    let u = position.z.atan2(position.x) / (2.0 * PI) + 0.5;
+    let v = position.y.clamp(-1.0, 1.0).asin() / PI + 0.5;
+    Vertex::new(position, position, Vec2::new(u, v))
+}
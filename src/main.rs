@@ -1,10 +1,9 @@
 // src/main.rs
 
-use nalgebra_glm::{Vec3, Mat4};
-use minifb::{Key, Window, WindowOptions};
+use nalgebra_glm::{Vec3, Vec4, Mat4};
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Window, WindowOptions};
 use std::time::Duration;
 use std::sync::Arc;
-use nalgebra_glm as glm;
 
 // Importa tus módulos aquí
 mod framebuffer;
@@ -17,17 +16,95 @@ mod shaders;
 mod camera;
 mod uniforms;
 mod renderer;
+mod terrain;
+mod clouds;
+mod atmosphere;
+mod geometry;
+mod random;
+mod star;
+mod gas_giant;
+mod rings;
+mod comet;
+mod particles;
+mod skybox;
+mod asteroids;
+mod craters;
+mod shadows;
+mod shadow_map;
+mod controls;
+mod camera_path;
+mod projection;
+mod origin;
+mod hud;
 
 use framebuffer::Framebuffer;
 use vertex::Vertex;
 use obj::Obj;
 use camera::Camera;
+use controls::{CameraController, CameraTransition};
+use camera_path::{CameraKeyframe, CameraPath};
+use projection::Projection;
+use origin::{FloatingOrigin, WorldPosition};
 use crate::color::Color;
-use fastnoise_lite::{FastNoiseLite, NoiseType, CellularDistanceFunction, FractalType, DomainWarpType};
-use renderer::render;
+use fastnoise_lite::{FastNoiseLite, NoiseType, CellularDistanceFunction, CellularReturnType, DomainWarpType, FractalType};
+use renderer::{render, render_with_blend, BlendMode};
+use atmosphere::AtmosphereSettings;
+use rings::{RingGap, RingProfile};
+use particles::{Emitter, EmitterShape, ParticleSystem};
+use skybox::Skybox;
+use asteroids::AsteroidBelt;
+use craters::CraterField;
+use shadows::Occluder;
+use shadow_map::ShadowMap;
 use fragment::CelestialType;
 use uniforms::Uniforms;
 
+// Identificador en el buffer de objetos del acompañante del cuerpo actual (ver `Companion`)
+const COMPANION_OBJECT_ID: u32 = 100;
+
+// Radios de los anillos (C a A de Saturno) y del cinturón de asteroides, que queda entre
+// las órbitas de Marte y del gigante gaseoso
+const RING_INNER_RATIO: f32 = 1.24; // En radios del planeta
+const RING_OUTER_RATIO: f32 = 2.27;
+const BELT_INNER_RADIUS: f32 = 2.2; // En unidades astronómicas
+const BELT_OUTER_RADIUS: f32 = 3.3;
+
+// Archivo donde 'R' guarda el recorrido grabado y desde el que 'P' lo reproduce
+const CAMERA_PATH_FILE: &str = "camera_path.txt";
+
+// Opciones de la línea de comandos:
+//   --play <recorrido>               reproduce un recorrido de cámara al arrancar
+//   --render <recorrido> <carpeta>   lo renderiza fuera de línea (un PPM por cuadro) y termina
+struct Options {
+    camera_path: Option<String>,
+    output_dir: Option<String>,
+}
+
+impl Options {
+    fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let mut options = Options { camera_path: None, output_dir: None };
+        match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+            [] => {}
+            ["--play", path] => options.camera_path = Some(path.to_string()),
+            ["--render", path, dir] => {
+                // La carpeta de salida se crea si no existe
+                if let Err(error) = std::fs::create_dir_all(dir) {
+                    eprintln!("No se pudo crear la carpeta {}: {}", dir, error);
+                    std::process::exit(1);
+                }
+                options.camera_path = Some(path.to_string());
+                options.output_dir = Some(dir.to_string());
+            }
+            _ => {
+                eprintln!("Uso: Lab4_CG [--play <recorrido> | --render <recorrido> <carpeta>]");
+                std::process::exit(2);
+            }
+        }
+        options
+    }
+}
+
 // Enumeración para los cuerpos celestes
 #[derive(Clone, PartialEq)]
 enum CelestialBody {
@@ -39,29 +116,185 @@ enum CelestialBody {
     Mars,
     Moon,
     Comet,
+    AsteroidBelt,
 }
 
 impl CelestialBody {
     fn to_celestial_type(&self) -> CelestialType {
         match self {
             CelestialBody::Star => CelestialType::Star,
-            CelestialBody::Planet => CelestialType::Planet,
+            CelestialBody::Planet | CelestialBody::Planet2 | CelestialBody::Mars => CelestialType::Rocky,
             CelestialBody::GasGiant => CelestialType::GasGiant,
             CelestialBody::Ringed => CelestialType::Ringed,
-            CelestialBody::Planet2 => CelestialType::Planet2,
-            CelestialBody::Mars => 
-            CelestialType::Mars,
             CelestialBody::Moon => CelestialType::Moon,
             CelestialBody::Comet => CelestialType::Comet,
+            CelestialBody::AsteroidBelt => CelestialType::Asteroid,
+        }
+    }
+
+    // Identificador en el buffer de objetos (0 queda para el fondo)
+    fn object_id(&self) -> u32 {
+        self.clone() as u32 + 1
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            CelestialBody::Star => "Estrella",
+            CelestialBody::Planet => "Planeta rocoso",
+            CelestialBody::GasGiant => "Gigante gaseoso",
+            CelestialBody::Ringed => "Planeta con anillos",
+            CelestialBody::Planet2 => "Planeta verde",
+            CelestialBody::Mars => "Marte",
+            CelestialBody::Moon => "Luna",
+            CelestialBody::Comet => "Cometa",
+            CelestialBody::AsteroidBelt => "Cinturón de asteroides",
+        }
+    }
+
+    // Escala de la malla en la vista del cuerpo (el cinturón no tiene malla propia)
+    fn scale(&self) -> f32 {
+        match self {
+            CelestialBody::Star => 3.0,
+            CelestialBody::Planet => 1.0,
+            CelestialBody::GasGiant => 1.5,
+            CelestialBody::Ringed => 1.5,
+            CelestialBody::Planet2 => 1.0,
+            CelestialBody::Mars => 0.8,
+            CelestialBody::Moon => 1.0,
+            CelestialBody::Comet => 0.5,
+            CelestialBody::AsteroidBelt => 1.0,
+        }
+    }
+
+    // Radio real del cuerpo en el mundo; el del cinturón es el de su borde exterior
+    fn radius(&self, planet_radius: f32) -> f32 {
+        match self {
+            CelestialBody::AsteroidBelt => BELT_OUTER_RADIUS * AU as f32,
+            _ => self.scale() * planet_radius,
+        }
+    }
+
+    // Radio que hay que encuadrar al enfocar el cuerpo, con los anillos si los tiene
+    fn framing_radius(&self, planet_radius: f32) -> f32 {
+        match self {
+            CelestialBody::Ringed => self.radius(planet_radius) * RING_OUTER_RATIO,
+            _ => self.radius(planet_radius),
+        }
+    }
+
+    // Parámetros propios del cuerpo que se muestran al seleccionarlo
+    fn details(&self) -> Vec<String> {
+        match self {
+            CelestialBody::Star => vec![
+                format!("temperatura {:.0} K", star::SUN.temperature),
+                format!("corona hasta {:.1} radios", star::SUN.corona_radius),
+            ],
+            CelestialBody::Planet => terrain_details(&terrain::EARTH),
+            CelestialBody::GasGiant => gas_giant_details(&gas_giant::JUPITER),
+            CelestialBody::Ringed => {
+                let mut details = gas_giant_details(&gas_giant::SATURN);
+                details.push(format!("anillos de {:.2} a {:.2} radios", RING_INNER_RATIO, RING_OUTER_RATIO));
+                details
+            }
+            CelestialBody::Planet2 => terrain_details(&terrain::VERDANT),
+            CelestialBody::Mars => terrain_details(&terrain::MARS),
+            CelestialBody::Moon => vec!["sin atmósfera, con cráteres".to_string()],
+            CelestialBody::Comet => vec![
+                format!("periodo {:.0} s, excentricidad {:.2}", comet::HALLEY.period, comet::HALLEY.eccentricity),
+                format!("semieje mayor {:.1} UA", comet::HALLEY.semi_major_axis),
+            ],
+            CelestialBody::AsteroidBelt => vec![format!("rocas entre {:.1} y {:.1} UA", BELT_INNER_RADIUS, BELT_OUTER_RADIUS)],
+        }
+    }
+}
+
+// Atmósfera y superficie de un planeta rocoso
+fn terrain_details(preset: &terrain::TerrainPreset) -> Vec<String> {
+    let mut details = vec![match &preset.atmosphere {
+        Some(atmosphere) => format!("atmósfera: Rayleigh {:.2}, Mie {:.2}", atmosphere.rayleigh_depth, atmosphere.mie_depth),
+        None => "sin atmósfera".to_string(),
+    }];
+    if preset.biomes.is_some() {
+        details.push("biomas por latitud y humedad".to_string());
+    }
+    details
+}
+
+fn gas_giant_details(preset: &gas_giant::GasGiantPreset) -> Vec<String> {
+    vec![format!("bandas: {}, vórtices: {}", preset.bands.len(), preset.vortices.len())]
+}
+
+// Lo que se puede seleccionar con un clic
+#[derive(Clone, PartialEq)]
+enum Selection {
+    Body(CelestialBody),
+    Companion, // El acompañante del cuerpo actual: se enfoca sin cambiar de vista
+}
+
+// Objeto que acompaña a un cuerpo en su vista (una luna).
+// Se dibuja y se enfoca en la misma escena; no es un cuerpo de la lista
+struct Companion {
+    name: &'static str,
+    position: Vec3, // Coordenadas locales
+    scale: f32,
+}
+
+// Acompañante del cuerpo en el instante `time`, si lo tiene
+fn companion(body: &CelestialBody, body_position: Vec3, time: f32, planet_radius: f32, light_direction: Vec3) -> Option<Companion> {
+    match body {
+        CelestialBody::GasGiant => {
+            // Luna interior cuya órbita pasa entre la estrella y el planeta: su sombra
+            // cruza las bandas y, al otro lado, la luna entra en la sombra del planeta
+            let across = Vec3::new(1.0, 0.0, -1.0).normalize();
+            let angle = time * 0.3;
+            let orbit_radius = planet_radius * body.scale() * 1.6;
+            Some(Companion {
+                name: "Luna del gigante gaseoso",
+                position: body_position + (across * angle.cos() + light_direction * angle.sin()) * orbit_radius,
+                scale: 0.15,
+            })
         }
+        CelestialBody::Planet2 => {
+            let orbit_radius = 2.0; // Distancia del planeta
+            let orbit_speed = 0.5;  // Velocidad orbital
+            let angle = time * orbit_speed;
+            Some(Companion {
+                name: "Luna del planeta verde",
+                position: body_position + Vec3::new(
+                    orbit_radius * angle.cos(),
+                    orbit_radius * angle.sin() * 0.1, // Pequeña inclinación orbital
+                    orbit_radius * angle.sin(),
+                ),
+                scale: 0.2, // La luna es más pequeña que el planeta
+            })
+        }
+        _ => None,
     }
 }
 
+// Ficha del objeto enfocado: nombre, radio real, distancia a la cámara y parámetros
+fn focus_info(body: &CelestialBody, companion: Option<&Companion>, rings: &RingProfile, planet_radius: f32, distance: f32) -> Vec<String> {
+    let (name, radius, details) = match companion {
+        Some(companion) => (companion.name, companion.scale * planet_radius, vec![format!("en la vista de: {}", body.name())]),
+        None => {
+            let mut details = body.details();
+            // Las divisiones de los anillos vienen del perfil cargado
+            if *body == CelestialBody::Ringed && !rings.gaps.is_empty() {
+                let names: Vec<&str> = rings.gaps.iter().map(|gap| gap.name.as_str()).collect();
+                details.push(format!("divisiones: {}", names.join(", ")));
+            }
+            (body.name(), body.radius(planet_radius), details)
+        }
+    };
+    let mut lines = vec![name.to_string(), format!("radio {:.2}", radius), format!("distancia {:.1}", distance)];
+    lines.extend(details);
+    lines
+}
+
 // Estructura para manejar los cuerpos visibles de manera secuencial
 struct BodyManager {
     all_bodies: Vec<CelestialBody>,
     current_index: usize,
-    zoom_level: f32,
 }
 
 impl BodyManager {
@@ -77,9 +310,9 @@ impl BodyManager {
 
                 CelestialBody::Moon,
                 CelestialBody::Comet,
+                CelestialBody::AsteroidBelt,
             ],
             current_index: 0,
-            zoom_level: 10.0, // Distancia inicial de la cámara
         }
     }
 
@@ -91,14 +324,6 @@ impl BodyManager {
         self.all_bodies[self.current_index].clone()
     }
 
-    fn zoom_in(&mut self) {
-        self.zoom_level = (self.zoom_level / 1.1).max(1.0); // Limitar zoom mínimo
-    }
-
-    fn zoom_out(&mut self) {
-        self.zoom_level *= 1.1;
-    }
-
     fn select(&mut self, index: usize) {
         if index < self.all_bodies.len() {
             self.current_index = index; // Establecer el índice actual al cuerpo seleccionado
@@ -106,10 +331,36 @@ impl BodyManager {
     }
 }
 
-// Función para obtener la posición del cuerpo celeste (todos en el origen)
-fn get_body_position(_body: &CelestialBody, _time: u32) -> Vec3 {
-    // Todos los cuerpos se renderizarán en el origen
-    Vec3::new(0.0, 0.0, 0.0)
+// Unidades de la escena por unidad astronómica: las distancias entre cuerpos son a escala
+// (los tamaños no), así que las posiciones necesitan f64 y el origen flotante
+const AU: f64 = 1.0e7;
+
+// Posición del cuerpo celeste en el mundo en el instante `time`, en doble precisión
+fn get_body_position(body: &CelestialBody, time: f32) -> WorldPosition {
+    let (x, y, z) = match body {
+        CelestialBody::Star => (0.0, 0.0, 0.0),
+        CelestialBody::Planet2 => (0.0, 0.0, 0.72),
+        CelestialBody::Planet => (1.0, 0.0, 0.0),
+        CelestialBody::Moon => (1.0, 0.0, 0.00257),
+        CelestialBody::Mars => (-1.45, 0.0, 0.45),
+        CelestialBody::GasGiant => (3.1, 0.0, -4.2),
+        CelestialBody::Ringed => (-6.7, 0.3, 6.7),
+        // El cometa recorre su órbita alrededor de la estrella
+        CelestialBody::Comet => return get_body_position(&CelestialBody::Star, time) + comet::HALLEY.position(time) * AU,
+        CelestialBody::AsteroidBelt => (0.0, 0.0, 0.0), // Anillo alrededor de la estrella
+    };
+    WorldPosition::new(x, y, z) * AU
+}
+
+// Dirección de la luz en un punto del mundo: hacia la estrella, con la resta en f64. En la
+// propia estrella no hay dirección definida y se usa una fija
+fn star_direction(position: &WorldPosition, time: f32) -> Vec3 {
+    let to_star = get_body_position(&CelestialBody::Star, time) - position;
+    if to_star.magnitude() > 0.0 {
+        to_star.normalize().cast::<f32>()
+    } else {
+        Vec3::new(1.0, 1.0, 1.0).normalize()
+    }
 }
 
 // Funciones para crear generadores de ruido específicos para cada cuerpo celeste
@@ -120,6 +371,18 @@ fn create_noise_star() -> Arc<FastNoiseLite> {
     Arc::new(noise)
 }
 
+// Granulación de la fotosfera: celdas con el dominio deformado
+fn create_noise_granulation() -> Arc<FastNoiseLite> {
+    let mut noise = FastNoiseLite::with_seed(1343);
+    noise.set_noise_type(Some(NoiseType::Cellular));
+    noise.set_cellular_distance_function(Some(CellularDistanceFunction::EuclideanSq));
+    noise.set_cellular_return_type(Some(CellularReturnType::Distance2Sub)); // Bordes oscuros entre celdas
+    noise.set_frequency(Some(1.0));
+    noise.set_domain_warp_type(Some(DomainWarpType::OpenSimplex2));
+    noise.set_domain_warp_amp(Some(0.6));
+    Arc::new(noise)
+}
+
 fn create_noise_planet() -> Arc<FastNoiseLite> {
     let mut noise = FastNoiseLite::with_seed(1338);
     noise.set_noise_type(Some(NoiseType::Perlin)); // Ruido Perlin para detalles medios
@@ -207,14 +470,9 @@ fn create_view_matrix(eye: Vec3, center: Vec3, up: Vec3) -> Mat4 {
     nalgebra_glm::look_at(&eye, &center, &up)
 }
 
-// Función para crear la matriz de perspectiva
-fn create_perspective_matrix(window_width: f32, window_height: f32) -> Mat4 {
-    let fov = 45.0 * std::f32::consts::PI / 180.0;
-    let aspect_ratio = window_width / window_height;
-    let near = 0.1;
-    let far = 1000.0;
-
-    nalgebra_glm::perspective(fov, aspect_ratio, near, far)
+// Función para crear la matriz de proyección de la cámara
+fn create_projection_matrix(window_width: f32, window_height: f32, projection: &Projection) -> Mat4 {
+    projection.matrix(window_width / window_height)
 }
 
 // Función para crear una matriz de rotación a partir de ángulos de Euler (en grados)
@@ -236,53 +494,117 @@ fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
     )
 }
 
+// Renderiza una capa esférica alrededor de un cuerpo con mezcla aditiva.
+// `radius` es el radio de la capa en unidades de la malla del cuerpo
+fn render_shell(
+    framebuffer: &mut Framebuffer,
+    shell_uniforms: &Uniforms,
+    shell_vertex_array: &[Vertex],
+    radius: f32,
+    celestial_type: CelestialType,
+) {
+    let mut shell_uniforms = shell_uniforms.clone();
+    shell_uniforms.model_matrix *= Mat4::new_scaling(radius);
+    shell_uniforms.terrain = None; // Las capas son esferas lisas, sin relieve
+
+    render_with_blend(framebuffer, &shell_uniforms, shell_vertex_array, celestial_type, BlendMode::Additive);
+}
+
+// Renderiza la capa de atmósfera de un cuerpo.
+// `body_radius` es el radio de la malla del cuerpo, antes de la matriz de modelo
+fn render_atmosphere(
+    framebuffer: &mut Framebuffer,
+    body_uniforms: &Uniforms,
+    shell_vertex_array: &[Vertex],
+    body_radius: f32,
+    settings: AtmosphereSettings,
+) {
+    let mut shell_uniforms = body_uniforms.clone();
+    shell_uniforms.atmosphere = Some(settings);
+
+    render_shell(framebuffer, &shell_uniforms, shell_vertex_array, body_radius * settings.radius, CelestialType::Atmosphere);
+}
+
 // Función para aplicar el post-procesamiento
 fn post_process(framebuffer: &mut Framebuffer) {
     for i in 0..framebuffer.buffer.len() {
         let emissive = framebuffer.emissive_buffer[i];
-        let final_color = blend_add(framebuffer.buffer[i], emissive);
-        framebuffer.buffer[i] = final_color;
+        if emissive == 0 {
+            continue;
+        }
+        // El brillo se suma en espacio lineal, como la luz; cada canal se satura en blanco
+        let glow = Color::from_hex(framebuffer.buffer[i]).to_linear() + Color::from_hex(emissive).to_linear();
+        framebuffer.buffer[i] = Color::from_linear(glow).to_hex();
     }
 }
 
-// Función para mezclar colores usando blend add
-fn blend_add(base: u32, emissive: u32) -> u32 {
-    let r = ((base >> 16) & 0xFF).saturating_add((emissive >> 16) & 0xFF);
-    let g = ((base >> 8) & 0xFF).saturating_add((emissive >> 8) & 0xFF);
-    let b = (base & 0xFF).saturating_add(emissive & 0xFF);
-    (r << 16) | (g << 8) | b
+// Selección con el ratón: un clic sin arrastrar sobre un cuerpo devuelve cuál es, leyendo
+// el buffer de objetos del cuadro anterior. `press` guarda dónde se pulsó el botón
+fn handle_click(window: &Window, framebuffer: &Framebuffer, body_manager: &BodyManager, press: &mut Option<(f32, f32)>) -> Option<Selection> {
+    let position = window.get_mouse_pos(MouseMode::Discard);
+    if window.get_mouse_down(MouseButton::Left) {
+        if press.is_none() {
+            *press = position;
+        }
+        return None;
+    }
+
+    let ((start_x, start_y), (x, y)) = (press.take()?, position?);
+    if (x - start_x).abs() + (y - start_y).abs() > 3.0 {
+        return None; // Fue un arrastre (trackball o mirar alrededor)
+    }
+
+    // La ventana puede tener otro tamaño que el framebuffer
+    let (width, height) = window.get_size();
+    let pixel_x = (x * framebuffer.width as f32 / width as f32) as usize;
+    let pixel_y = (y * framebuffer.height as f32 / height as f32) as usize;
+    let object = framebuffer.object_at(pixel_x, pixel_y)?;
+    if object == COMPANION_OBJECT_ID {
+        return Some(Selection::Companion);
+    }
+    body_manager.all_bodies.iter().find(|body| body.object_id() == object).cloned().map(Selection::Body)
 }
 
 // Función para manejar la entrada del usuario
-fn handle_input(window: &Window, camera: &mut Camera, body_manager: &mut BodyManager) {
-    let rotation_speed = 0.05; // Ajusta este valor para controlar la velocidad de rotación
-
-    // Cambiar al siguiente cuerpo celeste al presionar 'N'
-    if window.is_key_down(Key::N) {
+// (el movimiento de la cámara lo maneja el controlador activo)
+fn handle_input(
+    window: &mut Window,
+    camera: &mut Camera,
+    controller: &mut CameraController,
+    floating_origin: &mut FloatingOrigin,
+    follow_target: Vec3,
+    body_manager: &mut BodyManager,
+) {
+    // Cambiar al siguiente cuerpo celeste al presionar 'N' (una vez por pulsación)
+    if window.is_key_pressed(Key::N, KeyRepeat::No) {
         body_manager.next();
     }
 
-    // Zoom In y Zoom Out
-    if window.is_key_down(Key::Z) {
-        body_manager.zoom_in();
+    // Rueda del ratón: zoom con el campo de visión; 'O' alterna la vista ortográfica
+    if let Some((_, scroll)) = window.get_scroll_wheel() {
+        camera.projection.zoom(scroll);
     }
-
-    if window.is_key_down(Key::X) {
-        body_manager.zoom_out();
+    if window.is_key_pressed(Key::O, KeyRepeat::No) {
+        let distance = camera.distance();
+        camera.projection.toggle_orthographic(distance);
     }
 
-    // Rotación de la cámara
-    if window.is_key_down(Key::Up) {
-        camera.orbit(0.0, rotation_speed);
-    }
-    if window.is_key_down(Key::Down) {
-        camera.orbit(0.0, -rotation_speed);
+    // 'L' alterna la profundidad logarítmica
+    if window.is_key_pressed(Key::L, KeyRepeat::No) {
+        camera.projection.logarithmic_depth = !camera.projection.logarithmic_depth;
     }
-    if window.is_key_down(Key::Left) {
-        camera.orbit(-rotation_speed, 0.0);
+
+    // 'F' alterna el dibujo relativo a la cámara (origen flotante) y las coordenadas absolutas
+    if window.is_key_pressed(Key::F, KeyRepeat::No) {
+        floating_origin.camera_relative = !floating_origin.camera_relative;
+        let mode = if floating_origin.camera_relative { "relativo a la cámara" } else { "absoluto" };
+        window.set_title(&format!("Animated Fragment Shader - Origen: {}", mode));
     }
-    if window.is_key_down(Key::Right) {
-        camera.orbit(rotation_speed, 0.0);
+
+    // Cambiar de controlador de cámara (órbita, vuelo libre, seguimiento) con 'C'
+    if window.is_key_pressed(Key::C, KeyRepeat::No) {
+        *controller = controller.next(camera, follow_target);
+        window.set_title(&format!("Animated Fragment Shader - Cámara: {}", controller.name()));
     }
 
     // Selección de cuerpos celestes con teclas numéricas
@@ -295,16 +617,19 @@ fn handle_input(window: &Window, camera: &mut Camera, body_manager: &mut BodyMan
             5 => Key::Key5,
             6 => Key::Key6,
             7 => Key::Key7,
+            8 => Key::Key8,
+            9 => Key::Key9,
             _ => continue,
         };
 
-        if window.is_key_down(key) {
+        if window.is_key_pressed(key, KeyRepeat::No) {
             body_manager.select(num - 1); // Índice basado en cero
         }
     }
 }
 
 fn main() {
+    let options = Options::from_args();
     let window_width = 800;
     let window_height = 600;
     let framebuffer_width = 800;
@@ -312,18 +637,24 @@ fn main() {
     let frame_delay = Duration::from_millis(16); // Aproximadamente 60 FPS
 
     let mut framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
-    let mut window = Window::new(
-        "Animated Fragment Shader",
-        window_width,
-        window_height,
-        WindowOptions::default(),
-    )
-    .unwrap_or_else(|e| {
-        panic!("Failed to create window: {}", e);
-    });
 
-    window.set_position(500, 500);
-    window.update();
+    // Fuera de línea no se abre ventana: la cámara sale del recorrido y los cuadros van a disco
+    let offline = options.output_dir.is_some();
+    let mut window = (!offline).then(|| {
+        let mut window = Window::new(
+            "Animated Fragment Shader",
+            window_width,
+            window_height,
+            WindowOptions::default(),
+        )
+        .unwrap_or_else(|e| {
+            panic!("Failed to create window: {}", e);
+        });
+
+        window.set_position(500, 500);
+        window.update();
+        window
+    });
 
     framebuffer.set_background_color(0x000000);
 
@@ -336,6 +667,7 @@ fn main() {
 
     // Crear generadores de ruido separados para cada cuerpo celeste
     let noise_star = create_noise_star();
+    let noise_granulation = create_noise_granulation();
     let noise_planet = create_noise_planet();
     let noise_gas_giant = create_noise_gas_giant();
     let noise_moon = create_noise_moon();
@@ -355,56 +687,286 @@ fn main() {
     let ringed_obj = Obj::load("assets/models/planet.obj").expect("Failed to load ringed.obj");
     let ringed_vertex_array = ringed_obj.get_vertex_array();
 
-    let rings_obj = Obj::load("assets/models/rings2.obj").expect("Failed to load rings.obj");
-    let rings_vertex_array = rings_obj.get_vertex_array();
 
-    let moon_obj = Obj::load("assets/models/planet.obj").expect("Failed to load moon.obj");
-    let moon_vertex_array = moon_obj.get_vertex_array();
 
     let comet_obj = Obj::load("assets/models/planet.obj").expect("Failed to load comet.obj");
     let comet_vertex_array = comet_obj.get_vertex_array();
 
-    let nebula_obj = Obj::load("assets/models/planet.obj").expect("Failed to load nebula.obj");
-    let nebula_vertex_array = nebula_obj.get_vertex_array();
+    // Cinturón de asteroides entre dos órbitas alrededor de la estrella, en UA
+    let asteroid_belt = AsteroidBelt::generate(99, 1500, BELT_INNER_RADIUS, BELT_OUTER_RADIUS, 0.25, (0.025, 0.11));
+
+    // Fondo: estrellas y banda galáctica con el ruido de la nebulosa
+    let mut skybox = Skybox::new(2024, 3000, noise_nebula);
+
+    // Mapas de sombras que se conservan entre cuadros
+    let mut ringed_shadow: Option<Arc<ShadowMap>> = None;
+    let mut moon_shadow: Option<Arc<ShadowMap>> = None;
+
+    // Capa esférica para las atmósferas; el radio de la malla del planeta es irregular
+    let atmosphere_shell = geometry::icosphere(3);
+    let planet_radius = geometry::mean_radius(&planet_vertex_array);
+
+    // Esfera fina para los planetas rocosos con relieve desplazado
+    let mut terrain_vertex_array = geometry::icosphere(5);
+    for vertex in &mut terrain_vertex_array {
+        vertex.position *= planet_radius;
+    }
+
+    // Cráteres de la luna y del núcleo del cometa
+    let moon_craters = Arc::new(CraterField::generate(5, 600, 0.03, 0.3, 5));
+    let comet_craters = Arc::new(CraterField::generate(8, 120, 0.06, 0.35, 0));
 
-    let mut time = 0.0; // Usar f32 para mayor precisión en cálculos de tiempo
+    // Luna: icosfera fina con el relieve de los cráteres en la geometría, del tamaño del planeta
+    let mut moon_vertex_array = geometry::icosphere(5);
+    moon_craters.displace(&mut moon_vertex_array, &noise_moon, 1.0);
+    for vertex in &mut moon_vertex_array {
+        vertex.position *= planet_radius;
+    }
+    let star_radius = geometry::mean_radius(&star_vertex_array);
+    let prominences = star::generate_prominences(7, 6);
+
+    // Perfil radial de los anillos; si no se encuentra el archivo, se genera uno
+    let ring_profile = Arc::new(RingProfile::load("assets/rings/saturn.txt").unwrap_or_else(|e| {
+        eprintln!("No se pudo cargar el perfil de anillos ({}), se usa uno procedural", e);
+        let cassini = RingGap { name: "Cassini".to_string(), center: 0.73, width: 0.08, depth: 0.95 };
+        RingProfile::procedural(42, Color::new(225, 205, 170), vec![cassini])
+    }));
+
+    // Radios de los anillos en unidades de la malla del planeta
+    let ring_inner_radius = planet_radius * RING_INNER_RATIO;
+    let ring_outer_radius = planet_radius * RING_OUTER_RATIO;
+    let rings_vertex_array = geometry::annulus(ring_inner_radius, ring_outer_radius, 128, 16);
+
+    // Viento solar: partículas que salen radialmente de la superficie de la estrella
+    let mut solar_wind = ParticleSystem::new(11, 600);
+    solar_wind.emissive = true;
+    let mut wind_emitter = Emitter::new(Vec3::zeros(), EmitterShape::Sphere { radius: star_radius * 3.0 }, 120.0);
+    wind_emitter.speed = (1.5, 3.5);
+    wind_emitter.spread = 0.2;
+    wind_emitter.lifetime = (1.5, 3.0);
+    wind_emitter.size = (0.06, 0.18);
+    wind_emitter.color_start = Color::new(255, 190, 120);
+    wind_emitter.color_end = Color::new(40, 15, 5);
+    solar_wind.emitters.push(wind_emitter);
+
+    // Polvo del cometa: chorros hacia la estrella que la presión de radiación empuja hacia atrás
+    let mut comet_dust = ParticleSystem::new(12, 800);
+    comet_dust.emissive = true;
+    let mut dust_emitter = Emitter::new(Vec3::zeros(), EmitterShape::Point, 0.0);
+    dust_emitter.spread = 0.6;
+    dust_emitter.speed = (0.8, 1.6);
+    dust_emitter.lifetime = (2.0, 4.0);
+    dust_emitter.size = (0.05, 0.25);
+    dust_emitter.color_start = Color::new(200, 185, 150);
+    dust_emitter.color_end = Color::new(20, 18, 14);
+    comet_dust.emitters.push(dust_emitter);
+    let mut comet_world_position: Option<WorldPosition> = None; // Para mover el polvo con el núcleo
+
+    // Escombros de los anillos: partículas lentas que se desprenden del plano del anillo
+    let mut ring_debris = ParticleSystem::new(13, 400);
+    let mut debris_emitter = Emitter::new(Vec3::zeros(), EmitterShape::Point, 60.0);
+    debris_emitter.spread = 1.0;
+    debris_emitter.speed = (0.02, 0.08);
+    debris_emitter.lifetime = (3.0, 6.0);
+    debris_emitter.size = (0.03, 0.02);
+    debris_emitter.color_start = Color::new(90, 84, 74);
+    debris_emitter.color_end = Color::new(10, 9, 8);
+    ring_debris.emitters.push(debris_emitter);
+
+    let mut time: f32 = 0.0; // Usar f32 para mayor precisión en cálculos de tiempo
 
     // Inicializar BodyManager
     let mut body_manager = BodyManager::new();
 
-    while window.is_open() && !window.is_key_down(Key::Escape) {
+    // Controlador de la cámara y cuerpo que sigue en modo seguimiento
+    let mut controller = CameraController::orbit();
+    let mut follow_target = Vec3::zeros();
+    let mut transition: Option<CameraTransition> = None;
+
+    // Origen flotante relativo a la cámara ('F' lo alterna con coordenadas absolutas)
+    let mut floating_origin = FloatingOrigin::new(true);
+
+    // Posición donde se pulsó el botón del ratón, para distinguir un clic de un arrastre
+    let mut mouse_press: Option<(f32, f32)> = None;
+    // Si se ha enfocado el acompañante del cuerpo actual, y si se muestra la ficha del objeto enfocado
+    let mut companion_focus = false;
+    let mut show_info = false;
+
+    // Recorridos de cámara: el que se está grabando y el que se reproduce
+    let mut recording: Option<CameraPath> = None;
+    let mut playback: Option<CameraPath> = None;
+    if let Some(filename) = &options.camera_path {
+        let path = CameraPath::load(filename).unwrap_or_else(|error| {
+            eprintln!("No se pudo cargar el recorrido: {}", error);
+            std::process::exit(1);
+        });
+        time = path.start_time() - 0.016; // El primer cuadro cae en la primera clave
+        playback = Some(path);
+    }
+    let mut frame_number = 0;
+
+    while window.as_ref().is_none_or(|window| window.is_open() && !window.is_key_down(Key::Escape)) {
         time += 0.016; // Incrementar el tiempo en cada frame (aprox. 60 FPS)
 
-        // Manejar entradas
-        handle_input(&window, &mut camera, &mut body_manager);
+        // Manejar entradas (durante una reproducción solo se atiende 'P')
+        let previous_body = body_manager.current();
+        if let (Some(window), None) = (&mut window, &playback) {
+            handle_input(window, &mut camera, &mut controller, &mut floating_origin, follow_target, &mut body_manager);
+        }
 
-        framebuffer.clear();
+        // Clic sobre un cuerpo: se selecciona y se vuela hasta él (el acompañante se enfoca en su vista)
+        let picked = match (&window, &playback) {
+            (Some(window), None) => handle_click(window, &framebuffer, &body_manager, &mut mouse_press),
+            _ => None,
+        };
+        match &picked {
+            Some(Selection::Body(body)) => {
+                if let Some(index) = body_manager.all_bodies.iter().position(|other| other == body) {
+                    body_manager.select(index);
+                }
+                companion_focus = false;
+            }
+            Some(Selection::Companion) => companion_focus = true,
+            None => {}
+        }
+
+        // 'R' empieza o termina la grabación del recorrido, 'P' reproduce el último guardado
+        let key_pressed = |key| window.as_ref().is_some_and(|window| window.is_key_pressed(key, KeyRepeat::No));
+        if key_pressed(Key::R) {
+            match recording.take() {
+                Some(path) => {
+                    if let Err(error) = path.save(CAMERA_PATH_FILE) {
+                        eprintln!("No se pudo guardar el recorrido: {}", error);
+                    }
+                }
+                None => recording = Some(CameraPath::new()),
+            }
+        }
+        if key_pressed(Key::P) && playback.take().is_none() {
+            match CameraPath::load(CAMERA_PATH_FILE) {
+                Ok(path) => {
+                    time = path.start_time();
+                    playback = Some(path);
+                }
+                Err(error) => eprintln!("No se pudo cargar el recorrido: {}", error),
+            }
+        }
+
+        // En reproducción la cámara, el cuerpo y el tiempo vienen del recorrido
+        let mut keyframe = None;
+        if let Some(path) = &playback {
+            if time > path.end_time() + 1e-4 {
+                if offline {
+                    break;
+                }
+                playback = None;
+            } else {
+                keyframe = path.sample(time);
+            }
+        }
+        if let Some(key) = &keyframe {
+            body_manager.select(key.body);
+        }
 
-        // Obtener el cuerpo celeste actual
+        // El origen se lleva al ojo (o de vuelta al del mundo en modo absoluto), y con él
+        // todo lo que guarda coordenadas locales entre cuadros
+        if let Some(shift) = floating_origin.rebase(&camera.eye()) {
+            camera.translate(-shift);
+            follow_target -= shift;
+            if let Some(active) = &mut transition {
+                active.shift(-shift);
+            }
+            for system in [&mut solar_wind, &mut ring_debris, &mut comet_dust] {
+                system.shift(-shift);
+            }
+        }
+
+        // Obtener el cuerpo celeste actual, en coordenadas relativas al origen flotante
         let current_body = body_manager.current();
-        let body_position = get_body_position(&current_body, time as u32); // Convertir tiempo a u32
+        let body_world_position = get_body_position(&current_body, time);
+        let body_position = floating_origin.to_local(&body_world_position);
+        if current_body != previous_body && picked.is_none() {
+            companion_focus = false;
+            show_info = false;
+        }
+
+        // La luz llega desde la estrella del sistema
+        let light_direction = star_direction(&body_world_position, time);
+
+        // Objeto enfocado: el cuerpo o, si se ha elegido, su acompañante
+        let companion = companion(&current_body, body_position, time, planet_radius, light_direction);
+        let focused_companion = companion.as_ref().filter(|_| companion_focus);
+        let (focus_position, focus_radius) = match focused_companion {
+            Some(companion) => (companion.position, companion.scale * planet_radius),
+            None => (body_position, current_body.framing_radius(planet_radius)),
+        };
+
+        // Al cambiar de cuerpo se vuela hasta él en lugar de saltar
+        if (current_body != previous_body || picked.is_some()) && keyframe.is_none() {
+            transition = Some(CameraTransition::fly_to(&camera, focus_position, focus_radius, camera.projection.fov, 1.2));
+        }
+
+        // Ficha del objeto elegido con el ratón: en pantalla mientras siga enfocado, y en el título
+        if picked.is_some() {
+            show_info = true;
+            let distance = (camera.eye() - focus_position).magnitude();
+            let info = focus_info(&current_body, focused_companion, &ring_profile, planet_radius, distance).join(" | ");
+            if let Some(window) = &mut window {
+                window.set_title(&format!("Animated Fragment Shader - {}", info));
+            }
+        }
 
-        // Configurar la cámara para enfocar el cuerpo celeste actual
-        camera.center = body_position;
-        let direction = Vec3::new(0.0, 0.0, 1.0); // Dirección hacia el cuerpo celeste
-        camera.eye = body_position + direction * body_manager.zoom_level;
+        // El controlador activo mueve la cámara, salvo durante una transición. En seguimiento
+        // se sigue al acompañante si lo hay (las lunas se mueven)
+        follow_target = companion.as_ref().map_or(body_position, |companion| companion.position);
+        let target = match controller {
+            CameraController::Follow(_) => follow_target,
+            _ => focus_position,
+        };
+        match (&keyframe, &mut transition) {
+            (Some(key), _) => {
+                key.apply(&mut camera, &floating_origin);
+                transition = None;
+                controller.sync(&camera, target);
+            }
+            (None, Some(active)) => {
+                active.retarget(focus_position);
+                if !active.update(&mut camera, 0.016) {
+                    transition = None;
+                    controller.sync(&camera, target);
+                }
+            }
+            (None, None) => {
+                if let Some(window) = &window {
+                    controller.update(&mut camera, window, target, 0.016);
+                }
+            }
+        }
+
+        if let Some(path) = &mut recording {
+            path.push(CameraKeyframe::from_camera(&camera, &floating_origin, time, body_manager.current_index));
+        }
 
         // Crear matrices de transformación
-        let view_matrix = create_view_matrix(camera.eye, camera.center, camera.up);
-        let projection_matrix = create_perspective_matrix(window_width as f32, window_height as f32);
+        let view_matrix = create_view_matrix(camera.eye(), camera.center(), camera.up());
+        let projection_matrix = create_projection_matrix(window_width as f32, window_height as f32, &camera.projection);
         let viewport_matrix = create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32);
+        let log_depth = camera.projection.log_depth();
 
-        // Definir la dirección de la luz
-        let light_direction = Vec3::new(1.0, 1.0, 1.0).normalize();
+        framebuffer.reverse_z = camera.projection.reverse_z;
+        framebuffer.clear();
+        skybox.render(&mut framebuffer, &camera);
 
-        // Renderizar el cuerpo celeste actual
+        // Renderizar el cuerpo celeste actual. Lo que se dibuja se marca con su identificador
+        // en el buffer de objetos (los acompañantes lo cambian)
+        framebuffer.set_current_object(current_body.object_id());
         match current_body {
             CelestialBody::Star => {
                 let star_translation = body_position;
-                let star_rotation = Vec3::new(0.0, (time as f32 * 0.01).sin(), 0.0); // Rotación ejemplo
-                let star_scale = 3.0;
+                let star_rotation = Vec3::new(0.0, (time * 0.01).sin(), 0.0); // Rotación ejemplo
+                let star_scale = CelestialBody::Star.scale();
                 let star_model_matrix = create_model_matrix(star_translation, star_scale, star_rotation);
-                let star_uniforms = Uniforms::new(
+                let mut star_uniforms = Uniforms::new(
                     star_model_matrix,
                     view_matrix,
                     projection_matrix,
@@ -412,27 +974,35 @@ fn main() {
                     time,
                     noise_star.clone(),
                     light_direction,
-                    1.0,                        // noise_scale
-                    -0.6,                       // ocean_threshold
-                    0.65,                       // continent_threshold
-                    0.1,                        // mountain_threshold
-                    0.0,                        // snow_threshold
                     0.0,                        // ring_inner_radius
                     0.0,                        // ring_outer_radius
                     Color::black(),             // ring_color
                     0.0,                        // ring_opacity
                     0.0,                        // ring_frequency
                     0.0,                        // ring_wave_speed
-                    Mat4::identity(),           // ring_rotation_matrix
                 );
+                star_uniforms.log_depth = log_depth;
+                star_uniforms.camera_position = camera.eye();
+                star_uniforms.detail_noise = noise_granulation.clone();
+                star_uniforms.star = Some(star::SUN);
                 render(&mut framebuffer, &star_uniforms, &star_vertex_array, CelestialBody::Star.to_celestial_type());
+                render_shell(&mut framebuffer, &star_uniforms, &atmosphere_shell, star_radius * star::SUN.corona_radius, CelestialType::Corona);
+
+                // Protuberancias: billboards ya en espacio del mundo
+                let billboards = star::prominence_billboards(&prominences, &star_uniforms.model_matrix, star_radius, camera.eye(), time);
+                let mut prominence_uniforms = star_uniforms.clone();
+                prominence_uniforms.model_matrix = Mat4::identity();
+                render_with_blend(&mut framebuffer, &prominence_uniforms, &billboards, CelestialType::Prominence, BlendMode::Additive);
+
+                solar_wind.update(0.016);
+                solar_wind.render(&mut framebuffer, &star_uniforms);
             },
             CelestialBody::Planet => {
                 let planet_translation = body_position;
                 let planet_rotation = Vec3::new(0.0, (time * 0.02).sin(), 0.0); // Rotación ejemplo
-                let planet_scale = 1.0;
+                let planet_scale = CelestialBody::Planet.scale();
                 let planet_model_matrix = create_model_matrix(planet_translation, planet_scale, planet_rotation);
-                let planet_uniforms = Uniforms::new(
+                let mut planet_uniforms = Uniforms::new(
                     planet_model_matrix,
                     view_matrix,
                     projection_matrix,
@@ -440,27 +1010,29 @@ fn main() {
                     time,
                     noise_planet.clone(),
                     light_direction,
-                    3.0,                        // noise_scale
-                    -0.038,                     // ocean_threshold
-                    0.85,                       // continent_threshold
-                    0.2,                        // mountain_threshold
-                    0.05,                       // snow_threshold
                     0.0,                        // ring_inner_radius
                     0.0,                        // ring_outer_radius
                     Color::black(),             // ring_color
                     0.0,                        // ring_opacity
                     0.0,                        // ring_frequency
                     0.0,                        // ring_wave_speed
-                    Mat4::identity(),           // ring_rotation_matrix
                 );
-                render(&mut framebuffer, &planet_uniforms, &planet_vertex_array, CelestialBody::Planet.to_celestial_type());
+                planet_uniforms.log_depth = log_depth;
+                planet_uniforms.night_lights_intensity = 1.0; // Luces de ciudades en el lado nocturno
+                planet_uniforms.camera_position = camera.eye();
+                planet_uniforms.terrain = Some(&terrain::EARTH);
+                planet_uniforms.displacement = 0.1;
+                render(&mut framebuffer, &planet_uniforms, &terrain_vertex_array, CelestialBody::Planet.to_celestial_type());
+                if let Some(atmosphere) = terrain::EARTH.atmosphere {
+                    render_atmosphere(&mut framebuffer, &planet_uniforms, &atmosphere_shell, planet_radius, atmosphere);
+                }
             },
             CelestialBody::GasGiant => {
                 let gas_giant_translation = body_position;
                 let gas_giant_rotation = Vec3::new(0.0, (time * 0.02).sin(), 0.0); // Rotación ejemplo
-                let gas_giant_scale = 1.5;
+                let gas_giant_scale = CelestialBody::GasGiant.scale();
                 let gas_giant_model_matrix = create_model_matrix(gas_giant_translation, gas_giant_scale, gas_giant_rotation);
-                let gas_giant_uniforms = Uniforms::new(
+                let mut gas_giant_uniforms = Uniforms::new(
                     gas_giant_model_matrix,
                     view_matrix,
                     projection_matrix,
@@ -468,29 +1040,51 @@ fn main() {
                     time,
                     noise_gas_giant.clone(),
                     light_direction,
-                    15.0,                       // noise_scale
-                    -0.6,                       // ocean_threshold
-                    0.65,                       // continent_threshold
-                    0.1,                        // mountain_threshold
-                    0.0,                        // snow_threshold
                     0.0,                        // ring_inner_radius
                     0.0,                        // ring_outer_radius
                     Color::black(),             // ring_color
                     0.0,                        // ring_opacity
                     0.0,                        // ring_frequency
                     0.0,                        // ring_wave_speed
-                    Mat4::identity(),           // ring_rotation_matrix
                 );
+                gas_giant_uniforms.log_depth = log_depth;
+                gas_giant_uniforms.camera_position = camera.eye();
+
+                let moon = companion.as_ref().expect("el gigante gaseoso tiene una luna");
+                let (moon_translation, moon_scale) = (moon.position, moon.scale);
+                let moon_model_matrix = create_model_matrix(moon_translation, moon_scale, Vec3::new(0.0, time * 0.3, 0.0));
+                gas_giant_uniforms.occluders = vec![Occluder { center: moon_translation, radius: planet_radius * moon_scale }];
+
                 render(&mut framebuffer, &gas_giant_uniforms, &gas_giant_vertex_array, CelestialBody::GasGiant.to_celestial_type());
+
+                let mut moon_uniforms = Uniforms::new(
+                    moon_model_matrix, view_matrix, projection_matrix, viewport_matrix,
+                    time, noise_moon.clone(), light_direction,
+                    0.0, 0.0,
+                    Color::black(), 0.0, 0.0, 0.0,
+                );
+                moon_uniforms.log_depth = log_depth;
+                moon_uniforms.craters = Some(moon_craters.clone());
+                moon_uniforms.occluders = vec![Occluder { center: gas_giant_translation, radius: planet_radius * gas_giant_scale }];
+                framebuffer.set_current_object(COMPANION_OBJECT_ID);
+                render(&mut framebuffer, &moon_uniforms, &moon_vertex_array, CelestialType::Moon);
+
+                render_atmosphere(&mut framebuffer, &gas_giant_uniforms, &atmosphere_shell, planet_radius, gas_giant::JUPITER.atmosphere);
             },
             CelestialBody::Ringed => {
                 // Renderizar el planeta
                 let ringed_translation = body_position;
                 let ringed_rotation = Vec3::new(0.0, (time * 0.02).sin(), 0.0); // Rotación del planeta
-                let ringed_scale = 1.5;
+                let ringed_scale = CelestialBody::Ringed.scale();
                 let ringed_model_matrix = create_model_matrix(ringed_translation, ringed_scale, ringed_rotation);
 
-                let ringed_uniforms = Uniforms::new(
+                // Inclinación propia de los anillos respecto al planeta
+                let ring_pitch = 25.0; // Inclinación hacia atrás
+                let ring_yaw = 0.0;
+                let ring_roll = 10.0;  // Ligero giro en el eje Z
+                let ring_tilt = create_rotation_matrix(ring_pitch, ring_yaw, ring_roll);
+
+                let mut ringed_uniforms = Uniforms::new(
                     ringed_model_matrix,
                     view_matrix,
                     projection_matrix,
@@ -498,19 +1092,22 @@ fn main() {
                     time,
                     noise_gas_giant.clone(),
                     light_direction,
-                    15.0,                       // noise_scale
-                    -0.6,                       // ocean_threshold
-                    0.65,                       // continent_threshold
-                    0.1,                        // mountain_threshold
-                    0.0,                        // snow_threshold
-                    0.0,                        // ring_inner_radius
-                    0.0,                        // ring_outer_radius
-                    Color::black(),             // ring_color
-                    0.0,                        // ring_opacity
-                    0.0,                        // ring_frequency
-                    0.0,                        // ring_wave_speed
-                    Mat4::identity(),           // ring_rotation_matrix
+                    ring_inner_radius,
+                    ring_outer_radius,
+                    Color::new(255, 246, 232),  // ring_color
+                    0.9,                        // ring_opacity
+                    15.0,                       // ring_frequency
+                    0.5,                        // ring_wave_speed
                 );
+                ringed_uniforms.log_depth = log_depth;
+                ringed_uniforms.camera_position = camera.eye();
+                ringed_uniforms.ring_profile = Some(ring_profile.clone());
+                ringed_uniforms.ring_normal = (ring_tilt * Vec4::new(0.0, 1.0, 0.0, 0.0)).xyz().normalize();
+
+                // Mapa de sombras con la malla irregular del planeta, para su sombra sobre los anillos
+                // (se crea la primera vez y solo se vuelve a rasterizar si cambia la luz o el giro)
+                let ringed_shadow = ringed_shadow.get_or_insert_with(|| Arc::new(ShadowMap::new(1024, ring_outer_radius * ringed_scale * 1.05)));
+                Arc::make_mut(ringed_shadow).update(light_direction, &ringed_uniforms, &ringed_vertex_array);
 
                 render(
                     &mut framebuffer,
@@ -518,19 +1115,10 @@ fn main() {
                     &ringed_vertex_array,
                     CelestialType::Ringed, // Asegúrate que este es el tipo para el planeta
                 );
-                // Renderizar los anillos
-                let rings_translation = ringed_translation;
-                let rings_rotation = Vec3::new(0.0, 0.0, 0.0); // Rotación independiente si es necesario
-                let rings_scale = ringed_scale;
-                let rings_model_matrix = create_model_matrix(rings_translation, rings_scale, rings_rotation);
-
-                // Crear matriz de rotación para los anillos
-                let ring_pitch = 45.0; // Inclinación hacia atrás
-                let ring_yaw = 30.0;   // Giro hacia la derecha
-                let ring_roll = 0.0;   // Sin rotación en el eje Z
-                let ring_rotation_matrix = create_rotation_matrix(ring_pitch, ring_yaw, ring_roll);
-
-                let rings_uniforms = Uniforms::new(
+                // Renderizar los anillos: el plano XZ de la malla, inclinado junto con el planeta
+                let rings_model_matrix = ringed_model_matrix * ring_tilt;
+
+                let mut rings_uniforms = Uniforms::new(
                     rings_model_matrix,
                     view_matrix,
                     projection_matrix,
@@ -538,35 +1126,50 @@ fn main() {
                     time,
                     noise_gas_giant.clone(), // No se usa para anillos, pero es necesario
                     light_direction,
-                    0.0,                        // noise_scale (no usado para anillos)
-                    0.0,                        // ocean_threshold
-                    0.0,                        // continent_threshold
-                    0.0,                        // mountain_threshold
-                    0.0,                        // snow_threshold
-                    1.0,                        // ring_inner_radius
-                    4.0,                        // ring_outer_radius
-                    Color::new(200, 200, 200),  // ring_color
-                    0.7,                        // ring_opacity
+                    ring_inner_radius,
+                    ring_outer_radius,
+                    Color::new(255, 246, 232),  // ring_color
+                    0.9,                        // ring_opacity
                     15.0,                       // ring_frequency
                     0.5,                        // ring_wave_speed
-                    ring_rotation_matrix,
                 );
+                rings_uniforms.log_depth = log_depth;
+                rings_uniforms.ring_profile = Some(ring_profile.clone());
+                rings_uniforms.shadow_map = Some(ringed_shadow.clone());
 
-                render(
+                render_with_blend(
                     &mut framebuffer,
                     &rings_uniforms,
                     &rings_vertex_array,
                     CelestialType::Rings,
+                    BlendMode::Alpha,
                 );
+
+                // El emisor de escombros sigue el plano inclinado de los anillos
+                let debris_emitter = &mut ring_debris.emitters[0];
+                debris_emitter.position = ringed_translation;
+                debris_emitter.shape = EmitterShape::Ring {
+                    inner: ring_inner_radius * ringed_scale,
+                    outer: ring_outer_radius * ringed_scale,
+                    normal: (rings_model_matrix * Vec4::new(0.0, 1.0, 0.0, 0.0)).xyz().normalize(),
+                };
+                ring_debris.update(0.016);
+                ring_debris.render(&mut framebuffer, &rings_uniforms);
             },
             CelestialBody::Planet2 => {
                 // **Renderizar el Planeta**
                 let planet_translation = body_position;
                 let planet_rotation = Vec3::new(0.0, (time * 0.02).sin(), 0.0);
-                let planet_scale = 1.0;
+                let planet_scale = CelestialBody::Planet2.scale();
                 let planet_model_matrix = create_model_matrix(planet_translation, planet_scale, planet_rotation);
+
+                // **Posición orbital de la luna**
+                let moon = companion.as_ref().expect("el planeta verde tiene una luna");
+                let (moon_translation, moon_scale) = (moon.position, moon.scale);
+                let moon_rotation = Vec3::new(0.0, (time * 0.05).sin(), 0.0);
+                let moon_model_matrix = create_model_matrix(moon_translation, moon_scale, moon_rotation);
         
-                let planet_uniforms = Uniforms::new(
+                let mut planet_uniforms = Uniforms::new(
                     planet_model_matrix,
                     view_matrix,
                     projection_matrix,
@@ -574,43 +1177,28 @@ fn main() {
                     time,
                     noise_planet.clone(),
                     light_direction,
-                    3.0,                        // noise_scale
-                    -0.038,                     // ocean_threshold
-                    0.85,                       // continent_threshold
-                    0.2,                        // mountain_threshold
-                    0.05,                       // snow_threshold
                     0.0,                        // ring_inner_radius
                     0.0,                        // ring_outer_radius
                     Color::black(),             // ring_color
                     0.0,                        // ring_opacity
                     0.0,                        // ring_frequency
                     0.0,                        // ring_wave_speed
-                    Mat4::identity(),           // ring_rotation_matrix
                 );
+                planet_uniforms.log_depth = log_depth;
+                planet_uniforms.camera_position = camera.eye();
+                planet_uniforms.terrain = Some(&terrain::VERDANT);
+                planet_uniforms.displacement = 0.1;
+                // La luna y el planeta se eclipsan mutuamente
+                planet_uniforms.occluders = vec![Occluder { center: moon_translation, radius: planet_radius * moon_scale }];
         
                 render(
                     &mut framebuffer,
                     &planet_uniforms,
-                    &planet_vertex_array,
+                    &terrain_vertex_array,
                     CelestialBody::Planet2.to_celestial_type(),
                 );
         
-                // **Calcular la posición orbital de la luna**
-                let orbit_radius = 2.0; // Distancia del planeta
-                let orbit_speed = 0.5;  // Velocidad orbital
-                let angle = time * orbit_speed;
-        
-                let moon_translation = Vec3::new(
-                    planet_translation.x + orbit_radius * angle.cos(),
-                    planet_translation.y + orbit_radius * angle.sin() * 0.1, // Pequeña inclinación orbital
-                    planet_translation.z + orbit_radius * angle.sin(),
-                );
-        
-                let moon_rotation = Vec3::new(0.0, (time * 0.05).sin(), 0.0);
-                let moon_scale = 0.2; // La luna es más pequeña que el planeta
-                let moon_model_matrix = create_model_matrix(moon_translation, moon_scale, moon_rotation);
-        
-                let moon_uniforms = Uniforms::new(
+                let mut moon_uniforms = Uniforms::new(
                     moon_model_matrix,
                     view_matrix,
                     projection_matrix,
@@ -618,33 +1206,36 @@ fn main() {
                     time,
                     noise_moon.clone(),
                     light_direction,
-                    2.0,                        // noise_scale
-                    -0.5,                       // ocean_threshold
-                    0.6,                        // continent_threshold
-                    0.2,                        // mountain_threshold
-                    0.0,                        // snow_threshold
                     0.0,                        // ring_inner_radius
                     0.0,                        // ring_outer_radius
                     Color::black(),             // ring_color
                     0.0,                        // ring_opacity
                     0.0,                        // ring_frequency
                     0.0,                        // ring_wave_speed
-                    Mat4::identity(),           // ring_rotation_matrix
                 );
-                
+                moon_uniforms.log_depth = log_depth;
+                moon_uniforms.craters = Some(moon_craters.clone());
+                moon_uniforms.occluders = vec![Occluder { center: planet_translation, radius: planet_radius * planet_scale }];
+
+                framebuffer.set_current_object(COMPANION_OBJECT_ID);
                 render(
                     &mut framebuffer,
                     &moon_uniforms,
                     &planet_vertex_array, // Puedes usar el mismo modelo si no tienes uno específico para la luna
                     CelestialBody::Moon.to_celestial_type(),
                 );
+
+                // La atmósfera va al final para que también cubra a la luna cuando pasa por detrás
+                if let Some(atmosphere) = terrain::VERDANT.atmosphere {
+                    render_atmosphere(&mut framebuffer, &planet_uniforms, &atmosphere_shell, planet_radius, atmosphere);
+                }
             },
             CelestialBody::Mars => {
                 let planet_translation = body_position;
                 let planet_rotation = Vec3::new(0.0, (time * 0.02).sin(), 0.0); // Rotación ejemplo
-                let planet_scale = 0.8;
+                let planet_scale = CelestialBody::Mars.scale();
                 let planet_model_matrix = create_model_matrix(planet_translation, planet_scale, planet_rotation);
-                let planet_uniforms = Uniforms::new(
+                let mut planet_uniforms = Uniforms::new(
                     planet_model_matrix,
                     view_matrix,
                     projection_matrix,
@@ -652,40 +1243,50 @@ fn main() {
                     time,
                     noise_planet.clone(),
                     light_direction,
-                    6.0,                        // noise_scale
-                    -0.038,                     // ocean_threshold
-                    0.85,                       // continent_threshold
-                    0.2,                        // mountain_threshold
-                    0.05,                       // snow_threshold
                     0.0,                        // ring_inner_radius
                     0.0,                        // ring_outer_radius
                     Color::black(),             // ring_color
                     0.0,                        // ring_opacity
                     0.0,                        // ring_frequency
                     0.0,                        // ring_wave_speed
-                    Mat4::identity(),           // ring_rotation_matrix
                 );
-                render(&mut framebuffer, &planet_uniforms, &planet_vertex_array, CelestialBody::Mars.to_celestial_type());
+                planet_uniforms.log_depth = log_depth;
+                planet_uniforms.camera_position = camera.eye();
+                planet_uniforms.terrain = Some(&terrain::MARS);
+                planet_uniforms.displacement = 0.1;
+                render(&mut framebuffer, &planet_uniforms, &terrain_vertex_array, CelestialBody::Mars.to_celestial_type());
+                if let Some(atmosphere) = terrain::MARS.atmosphere {
+                    render_atmosphere(&mut framebuffer, &planet_uniforms, &atmosphere_shell, planet_radius, atmosphere);
+                }
             },
             CelestialBody::Moon => {
                 let moon_translation = body_position;
                 let moon_rotation = Vec3::new(0.0, (time * 0.02).sin(), 0.0);
-                let moon_scale = 1.0;
+                let moon_scale = CelestialBody::Moon.scale();
                 let moon_model_matrix = create_model_matrix(moon_translation, moon_scale, moon_rotation);
-                let moon_uniforms = Uniforms::new(
+                let mut moon_uniforms = Uniforms::new(
                     moon_model_matrix, view_matrix, projection_matrix, viewport_matrix,
                     time, noise_moon.clone(), light_direction,
-                    2.0, -0.5, 0.6, 0.2, 0.0, 0.0, 0.0,
-                    Color::black(), 0.0, 0.0, 0.0, Mat4::identity(),
+                    0.0, 0.0,
+                    Color::black(), 0.0, 0.0, 0.0,
                 );
+                moon_uniforms.log_depth = log_depth;
+                moon_uniforms.craters = Some(moon_craters.clone());
+
+                // Mapa de sombras de la propia malla: los bordes de los cráteres sombrean los cuencos
+                let moon_shadow = moon_shadow.get_or_insert_with(|| Arc::new(ShadowMap::new(1024, planet_radius * moon_scale * 1.2)));
+                Arc::make_mut(moon_shadow).update(light_direction, &moon_uniforms, &moon_vertex_array);
+                moon_uniforms.shadow_map = Some(moon_shadow.clone());
                 render(&mut framebuffer, &moon_uniforms, &moon_vertex_array, CelestialBody::Moon.to_celestial_type());
             },
             CelestialBody::Comet => {
                 let comet_translation = body_position;
                 let comet_rotation = Vec3::new(0.0, (time * 0.03).sin(), 0.0); // Rotación ejemplo
-                let comet_scale = 0.5;
+                let comet_scale = CelestialBody::Comet.scale();
                 let comet_model_matrix = create_model_matrix(comet_translation, comet_scale, comet_rotation);
-                let comet_uniforms = Uniforms::new(
+                // Actividad y colas según la posición en la órbita
+                let comet_state = comet::HALLEY.state(time);
+                let mut comet_uniforms = Uniforms::new(
                     comet_model_matrix,
                     view_matrix,
                     projection_matrix,
@@ -693,30 +1294,101 @@ fn main() {
                     time,
                     noise_comet.clone(),
                     light_direction,
-                    7.0,                        // noise_scale
-                    -0.6,                       // ocean_threshold
-                    0.65,                       // continent_threshold
-                    0.1,                        // mountain_threshold
-                    0.0,                        // snow_threshold
                     0.0,                        // ring_inner_radius
                     0.0,                        // ring_outer_radius
                     Color::black(),             // ring_color
                     0.0,                        // ring_opacity
                     0.0,                        // ring_frequency
                     0.0,                        // ring_wave_speed
-                    Mat4::identity(),           // ring_rotation_matrix
                 );
+                comet_uniforms.log_depth = log_depth;
+                comet_uniforms.comet = Some(comet_state);
+                comet_uniforms.craters = Some(comet_craters.clone());
                 render(&mut framebuffer, &comet_uniforms, &comet_vertex_array, CelestialBody::Comet.to_celestial_type());
+
+                // Coma y colas: billboards emisivos ya en espacio del mundo
+                let nucleus_radius = geometry::mean_radius(&comet_vertex_array) * comet_scale;
+                let comet_geometry = comet_state.build_geometry(comet_translation, nucleus_radius, camera.eye());
+                let mut glow_uniforms = comet_uniforms.clone();
+                glow_uniforms.model_matrix = Mat4::identity();
+                render_with_blend(&mut framebuffer, &glow_uniforms, &comet_geometry.dust_tail, CelestialType::DustTail, BlendMode::Additive);
+                render_with_blend(&mut framebuffer, &glow_uniforms, &comet_geometry.ion_tail, CelestialType::IonTail, BlendMode::Additive);
+                render_with_blend(&mut framebuffer, &glow_uniforms, &comet_geometry.coma, CelestialType::Coma, BlendMode::Additive);
+
+                // El polvo ya emitido acompaña al núcleo en la órbita, y la emisión sigue la
+                // actividad del cometa
+                if let Some(previous) = comet_world_position.replace(body_world_position) {
+                    comet_dust.shift((body_world_position - previous).cast::<f32>());
+                }
+                let dust_emitter = &mut comet_dust.emitters[0];
+                dust_emitter.position = comet_translation;
+                dust_emitter.direction = comet_state.sun_direction;
+                dust_emitter.acceleration = -comet_state.sun_direction * 2.5;
+                dust_emitter.rate = 250.0 * comet_state.activity;
+                comet_dust.update(0.016);
+                comet_dust.render(&mut framebuffer, &comet_uniforms);
+            },
+            CelestialBody::AsteroidBelt => {
+                // La estrella del sistema en el centro, agrandada para que se vea a la escala
+                // del cinturón; un clic sobre ella la selecciona como cuerpo
+                let star_scale = BELT_INNER_RADIUS * AU as f32 * 0.2 / star_radius;
+                let star_model_matrix = create_model_matrix(body_position, star_scale, Vec3::new(0.0, time * 0.01, 0.0));
+                let mut star_uniforms = Uniforms::new(
+                    star_model_matrix, view_matrix, projection_matrix, viewport_matrix,
+                    time, noise_star.clone(), light_direction,
+                    0.0, 0.0,
+                    Color::black(), 0.0, 0.0, 0.0,
+                );
+                star_uniforms.log_depth = log_depth;
+                star_uniforms.camera_position = camera.eye();
+                star_uniforms.detail_noise = noise_granulation.clone();
+                star_uniforms.star = Some(star::SUN);
+                framebuffer.set_current_object(CelestialBody::Star.object_id());
+                render(&mut framebuffer, &star_uniforms, &star_vertex_array, CelestialType::Star);
+                render_shell(&mut framebuffer, &star_uniforms, &atmosphere_shell, star_radius * star::SUN.corona_radius, CelestialType::Corona);
+                framebuffer.set_current_object(CelestialBody::AsteroidBelt.object_id());
+
+                // Rocas iluminadas desde la estrella; el plano del cinturón se inclina hacia la cámara
+                // y se escala de UA a unidades del mundo
+                let belt_matrix = Mat4::new_translation(&body_position)
+                    * Mat4::new_scaling(AU as f32)
+                    * create_rotation_matrix(35.0, 0.0, 0.0);
+                let mut belt_uniforms = Uniforms::new(
+                    belt_matrix, view_matrix, projection_matrix, viewport_matrix,
+                    time, noise_moon.clone(), light_direction,
+                    0.0, 0.0,
+                    Color::black(), 0.0, 0.0, 0.0,
+                );
+                belt_uniforms.log_depth = log_depth;
+                belt_uniforms.light_position = Some(body_position);
+                asteroid_belt.render(&mut framebuffer, &belt_uniforms, &belt_matrix);
             },
         }
 
         // Post-Procesamiento para Emisión
         post_process(&mut framebuffer);
 
-        window
-            .update_with_buffer(&framebuffer.buffer, framebuffer_width, framebuffer_height)
-            .unwrap();
+        // Ficha del objeto enfocado, encima de la imagen final
+        if show_info {
+            let distance = (camera.eye() - focus_position).magnitude();
+            hud::draw_panel(&mut framebuffer, &focus_info(&current_body, focused_companion, &ring_profile, planet_radius, distance));
+        }
+
+        // Fuera de línea cada cuadro se guarda en disco y no se espera entre cuadros
+        if let Some(dir) = &options.output_dir {
+            let filename = format!("{}/frame_{:05}.ppm", dir, frame_number);
+            if let Err(error) = framebuffer.save_ppm(&filename) {
+                eprintln!("No se pudo guardar el cuadro {}: {}", filename, error);
+                std::process::exit(1);
+            }
+            frame_number += 1;
+        }
 
-        std::thread::sleep(frame_delay);
+        if let Some(window) = &mut window {
+            window
+                .update_with_buffer(&framebuffer.buffer, framebuffer_width, framebuffer_height)
+                .unwrap();
+            std::thread::sleep(frame_delay);
+        }
     }
 }
@@ -0,0 +1,44 @@
+// src/origin.rs
+
+use nalgebra::Vector3;
+use nalgebra_glm::Vec3;
+
+/// Posición absoluta en el mundo, en doble precisión: a escala real un f32 no distingue
+/// metros a 10^9 km del origen
+pub type WorldPosition = Vector3<f64>;
+
+/// Origen flotante: todo se dibuja en coordenadas locales (f32) relativas a un origen en
+/// f64. En modo relativo a la cámara el origen se lleva al ojo en cada cuadro, así los
+/// números que llegan al `vertex_shader` son pequeños; si no, queda en el origen del mundo
+pub struct FloatingOrigin {
+    pub origin: WorldPosition,
+    pub camera_relative: bool,
+}
+
+impl FloatingOrigin {
+    pub fn new(camera_relative: bool) -> Self {
+        FloatingOrigin { origin: WorldPosition::zeros(), camera_relative }
+    }
+
+    /// Posición del mundo en coordenadas locales (la resta se hace en f64)
+    pub fn to_local(&self, world: &WorldPosition) -> Vec3 {
+        (world - self.origin).cast::<f32>()
+    }
+
+    pub fn to_world(&self, local: &Vec3) -> WorldPosition {
+        self.origin + local.cast::<f64>()
+    }
+
+    /// Lleva el origen al ojo (en coordenadas locales), o de vuelta al origen del mundo si el
+    /// modo relativo a la cámara está desactivado. Devuelve el desplazamiento que hay que
+    /// restar a todo lo que guarda coordenadas locales
+    pub fn rebase(&mut self, eye: &Vec3) -> Option<Vec3> {
+        let origin = if self.camera_relative { self.to_world(eye) } else { WorldPosition::zeros() };
+        if origin == self.origin {
+            return None;
+        }
+        let shift = (origin - self.origin).cast::<f32>();
+        self.origin = origin;
+        Some(shift)
+    }
+}
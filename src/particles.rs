@@ -0,0 +1,217 @@
+// src/particles.rs
+
+use nalgebra_glm::{Vec3, Vec4};
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+use crate::random::Rng;
+use crate::Uniforms;
+
+/// Partícula simulada en la CPU (posiciones en espacio del mundo)
+pub struct Particle {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub acceleration: Vec3,
+    pub age: f32,
+    pub lifetime: f32,
+    pub size_start: f32,
+    pub size_end: f32,
+    pub color_start: Color,
+    pub color_end: Color,
+}
+
+impl Particle {
+    // Fracción de vida consumida, en [0, 1]
+    fn progress(&self) -> f32 {
+        (self.age / self.lifetime).clamp(0.0, 1.0)
+    }
+
+    pub fn size(&self) -> f32 {
+        self.size_start + (self.size_end - self.size_start) * self.progress()
+    }
+
+    pub fn color(&self) -> Color {
+        self.color_start.lerp(&self.color_end, self.progress())
+    }
+}
+
+/// Forma de la región donde nacen las partículas
+#[derive(Clone, Copy)]
+pub enum EmitterShape {
+    Point,
+    Sphere { radius: f32 },   // Sobre la superficie de una esfera
+    Ring { inner: f32, outer: f32, normal: Vec3 },
+}
+
+/// Emisor de partículas. Si `direction` es cero, la velocidad inicial es radial
+/// respecto a `position` (viento solar, polvo que se escapa de un cuerpo)
+pub struct Emitter {
+    pub position: Vec3,
+    pub shape: EmitterShape,
+    pub rate: f32,           // Partículas por segundo
+    pub direction: Vec3,
+    pub spread: f32,         // 0 = sin dispersión, 1 = dirección aleatoria
+    pub speed: (f32, f32),
+    pub acceleration: Vec3,  // Aceleración de las partículas nuevas (presión de radiación, gravedad)
+    pub lifetime: (f32, f32),
+    pub size: (f32, f32),    // Tamaño al nacer y al morir, en unidades del mundo
+    pub color_start: Color,
+    pub color_end: Color,
+    accumulator: f32,
+}
+
+impl Emitter {
+    pub fn new(position: Vec3, shape: EmitterShape, rate: f32) -> Self {
+        Emitter {
+            position,
+            shape,
+            rate,
+            direction: Vec3::zeros(),
+            spread: 0.0,
+            speed: (1.0, 1.0),
+            acceleration: Vec3::zeros(),
+            lifetime: (1.0, 1.0),
+            size: (0.1, 0.1),
+            color_start: Color::new(255, 255, 255),
+            color_end: Color::black(),
+            accumulator: 0.0,
+        }
+    }
+
+    fn spawn(&self, rng: &mut Rng) -> Particle {
+        let (offset, outward) = match self.shape {
+            EmitterShape::Point => (Vec3::zeros(), rng.unit_vector()),
+            EmitterShape::Sphere { radius } => {
+                let direction = rng.unit_vector();
+                (direction * radius, direction)
+            }
+            EmitterShape::Ring { inner, outer, normal } => {
+                // Punto aleatorio en el plano del anillo
+                let mut direction = rng.unit_vector();
+                direction -= normal * normal.dot(&direction);
+                let direction = if direction.magnitude() > 1e-4 { direction.normalize() } else { normal.cross(&Vec3::x()).normalize() };
+                (direction * rng.range(inner, outer), direction)
+            }
+        };
+
+        let base = if self.direction.magnitude() > 0.0 { self.direction.normalize() } else { outward };
+        let direction = (base * (1.0 - self.spread) + rng.unit_vector() * self.spread).normalize();
+
+        Particle {
+            position: self.position + offset,
+            velocity: direction * rng.range(self.speed.0, self.speed.1),
+            acceleration: self.acceleration,
+            age: 0.0,
+            lifetime: rng.range(self.lifetime.0, self.lifetime.1),
+            size_start: self.size.0,
+            size_end: self.size.1,
+            color_start: self.color_start,
+            color_end: self.color_end,
+        }
+    }
+}
+
+/// Conjunto de emisores y partículas vivas, dibujadas como sprites aditivos
+pub struct ParticleSystem {
+    pub emitters: Vec<Emitter>,
+    pub particles: Vec<Particle>,
+    pub max_particles: usize,
+    pub emissive: bool, // Si es verdadero, las partículas se dibujan en el buffer emisivo
+    rng: Rng,
+}
+
+impl ParticleSystem {
+    pub fn new(seed: u64, max_particles: usize) -> Self {
+        ParticleSystem {
+            emitters: Vec::new(),
+            particles: Vec::with_capacity(max_particles),
+            max_particles,
+            emissive: false,
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// Desplaza partículas y emisores (al cambiar el origen flotante)
+    pub fn shift(&mut self, offset: Vec3) {
+        for particle in &mut self.particles {
+            particle.position += offset;
+        }
+        for emitter in &mut self.emitters {
+            emitter.position += offset;
+        }
+    }
+
+    /// Avanza la simulación `dt` segundos: envejece, mueve y crea partículas
+    pub fn update(&mut self, dt: f32) {
+        for particle in &mut self.particles {
+            particle.age += dt;
+        }
+        self.particles.retain(|p| p.age < p.lifetime);
+
+        for emitter in &mut self.emitters {
+            emitter.accumulator += emitter.rate * dt;
+            while emitter.accumulator >= 1.0 {
+                emitter.accumulator -= 1.0;
+                if self.particles.len() < self.max_particles {
+                    self.particles.push(emitter.spawn(&mut self.rng));
+                }
+            }
+        }
+
+        for particle in &mut self.particles {
+            particle.velocity += particle.acceleration * dt;
+            particle.position += particle.velocity * dt;
+        }
+    }
+
+    /// Dibuja cada partícula como un disco difuso con prueba de profundidad y mezcla aditiva
+    pub fn render(&self, framebuffer: &mut Framebuffer, uniforms: &Uniforms) {
+        let view_projection = uniforms.projection_matrix * uniforms.view_matrix;
+        let scale_x = uniforms.projection_matrix[(0, 0)] * uniforms.viewport_matrix[(0, 0)];
+        let scale_y = uniforms.projection_matrix[(1, 1)] * uniforms.viewport_matrix[(1, 1)].abs();
+
+        for particle in &self.particles {
+            let clip = view_projection * Vec4::new(particle.position.x, particle.position.y, particle.position.z, 1.0);
+            if clip.w <= 0.0 {
+                continue; // Detrás de la cámara
+            }
+            let depth = uniforms.log_depth.map_or(clip.z / clip.w, |log_depth| log_depth.depth(clip.w));
+            let ndc = Vec4::new(clip.x / clip.w, clip.y / clip.w, depth, 1.0);
+            let screen = uniforms.viewport_matrix * ndc;
+
+            let size = particle.size();
+            let radius_x = (size * scale_x / clip.w).max(0.5);
+            let radius_y = (size * scale_y / clip.w).max(0.5);
+            let color = particle.color();
+            splat(framebuffer, screen.x, screen.y, screen.z, radius_x, radius_y, color, self.emissive);
+        }
+    }
+}
+
+// Sprite elíptico en pantalla con caída gaussiana desde el centro
+#[allow(clippy::too_many_arguments)]
+fn splat(framebuffer: &mut Framebuffer, cx: f32, cy: f32, depth: f32, radius_x: f32, radius_y: f32, color: Color, emissive: bool) {
+    let min_x = (cx - radius_x).floor().max(0.0) as usize;
+    let max_x = (cx + radius_x).ceil().min(framebuffer.width as f32 - 1.0);
+    let min_y = (cy - radius_y).floor().max(0.0) as usize;
+    let max_y = (cy + radius_y).ceil().min(framebuffer.height as f32 - 1.0);
+    if max_x < 0.0 || max_y < 0.0 {
+        return;
+    }
+
+    for y in min_y..=max_y as usize {
+        for x in min_x..=max_x as usize {
+            let dx = (x as f32 + 0.5 - cx) / radius_x;
+            let dy = (y as f32 + 0.5 - cy) / radius_y;
+            let distance_squared = dx * dx + dy * dy;
+            if distance_squared > 1.0 {
+                continue;
+            }
+
+            let falloff = (-distance_squared * 3.0).exp();
+            let sprite_color = color * falloff;
+            let (surface, emission) = if emissive { (Color::black(), sprite_color) } else { (sprite_color, Color::black()) };
+            framebuffer.set_current_color(surface.to_hex());
+            framebuffer.point_additive(x, y, depth, emission.to_hex());
+        }
+    }
+}
@@ -0,0 +1,131 @@
+// src/projection.rs
+
+use nalgebra_glm::Mat4;
+use std::f32::consts::PI;
+
+const MIN_FOV: f32 = 5.0 * PI / 180.0;
+const MAX_FOV: f32 = 120.0 * PI / 180.0;
+const ORTHOGRAPHIC_FAR: f32 = 1.0e5; // Límite lejano en ortográfica si no hay plano lejano
+const LOGARITHMIC_FAR: f32 = 1.0e12; // Distancia que llega a profundidad 1 (o 0) en logarítmica si no hay plano lejano
+
+/// Tipo de proyección de la cámara
+#[derive(Clone, Copy, PartialEq)]
+pub enum ProjectionKind {
+    Perspective,
+    Orthographic, // Sin perspectiva, para diagramas
+}
+
+/// Proyección de la cámara. Con `reverse_z` la profundidad va de 1 en el plano cercano a 0
+/// en el lejano, lo que reparte mejor la precisión del f32 a escala del sistema solar
+/// (el z-buffer debe usar el mismo criterio, ver `Framebuffer::reverse_z`)
+#[derive(Clone, Copy)]
+pub struct Projection {
+    pub kind: ProjectionKind,
+    pub fov: f32,        // Campo de visión vertical en perspectiva (radianes)
+    pub half_height: f32, // Media altura visible en ortográfica (unidades del mundo)
+    pub near: f32,
+    pub far: Option<f32>, // None = plano lejano en el infinito
+    pub reverse_z: bool,
+    pub logarithmic_depth: bool, // Profundidad según log(1 + distancia), solo en perspectiva
+}
+
+/// Profundidad logarítmica: la precisión se reparte por órdenes de magnitud de la distancia,
+/// así conviven objetos a metros y a millones de km sin z-fighting
+#[derive(Clone, Copy)]
+pub struct LogDepth {
+    pub far: f32,
+    pub reverse_z: bool,
+}
+
+impl LogDepth {
+    /// Profundidad en [0, 1] para una distancia `w` delante de la cámara
+    pub fn depth(&self, w: f32) -> f32 {
+        let depth = (1.0 + w.max(0.0)).log2() / (1.0 + self.far).log2();
+        if self.reverse_z { 1.0 - depth } else { depth }
+    }
+}
+
+impl Projection {
+    pub fn new() -> Self {
+        Projection {
+            kind: ProjectionKind::Perspective,
+            fov: 45.0 * PI / 180.0,
+            half_height: 5.0,
+            near: 0.1,
+            far: None,
+            reverse_z: true,
+            logarithmic_depth: false,
+        }
+    }
+
+    /// Parámetros de la profundidad logarítmica, si está activa
+    pub fn log_depth(&self) -> Option<LogDepth> {
+        (self.logarithmic_depth && self.kind == ProjectionKind::Perspective).then(|| LogDepth {
+            far: self.far.unwrap_or(LOGARITHMIC_FAR),
+            reverse_z: self.reverse_z,
+        })
+    }
+
+    /// Matriz de proyección para una relación de aspecto (ancho / alto)
+    pub fn matrix(&self, aspect_ratio: f32) -> Mat4 {
+        let (near, far) = (self.near, self.far);
+        let mut matrix = Mat4::zeros();
+        match self.kind {
+            ProjectionKind::Perspective => {
+                let f = 1.0 / (self.fov * 0.5).tan();
+                matrix[(0, 0)] = f / aspect_ratio;
+                matrix[(1, 1)] = f;
+                matrix[(3, 2)] = -1.0; // w = -z (distancia delante de la cámara)
+
+                // Fila de z: profundidad en NDC, [-1, 1] normal o [1, 0] invertida
+                let (a, b) = match (self.reverse_z, far) {
+                    (true, Some(far)) => (near / (far - near), near * far / (far - near)),
+                    (true, None) => (0.0, near),
+                    (false, Some(far)) => (-(far + near) / (far - near), -2.0 * far * near / (far - near)),
+                    (false, None) => (-1.0, -2.0 * near),
+                };
+                matrix[(2, 2)] = a;
+                matrix[(2, 3)] = b;
+            }
+            ProjectionKind::Orthographic => {
+                let far = far.unwrap_or(ORTHOGRAPHIC_FAR);
+                matrix[(0, 0)] = 1.0 / (self.half_height * aspect_ratio);
+                matrix[(1, 1)] = 1.0 / self.half_height;
+                matrix[(3, 3)] = 1.0;
+
+                let (a, b) = if self.reverse_z {
+                    (1.0 / (far - near), far / (far - near))
+                } else {
+                    (-2.0 / (far - near), -(far + near) / (far - near))
+                };
+                matrix[(2, 2)] = a;
+                matrix[(2, 3)] = b;
+            }
+        }
+        matrix
+    }
+
+    /// Zoom con la rueda del ratón: estrecha o abre el campo de visión (o la altura visible
+    /// en ortográfica). `steps` positivo acerca
+    pub fn zoom(&mut self, steps: f32) {
+        let factor = 0.9_f32.powf(steps);
+        match self.kind {
+            ProjectionKind::Perspective => self.fov = (self.fov * factor).clamp(MIN_FOV, MAX_FOV),
+            ProjectionKind::Orthographic => self.half_height = (self.half_height * factor).max(1e-3),
+        }
+    }
+
+    /// Alterna entre perspectiva y ortográfica conservando el tamaño aparente de lo que
+    /// está a `focus_distance` de la cámara
+    pub fn toggle_orthographic(&mut self, focus_distance: f32) {
+        match self.kind {
+            ProjectionKind::Perspective => {
+                self.half_height = focus_distance * (self.fov * 0.5).tan();
+                self.kind = ProjectionKind::Orthographic;
+            }
+            ProjectionKind::Orthographic => {
+                self.kind = ProjectionKind::Perspective;
+            }
+        }
+    }
+}
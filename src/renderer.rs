@@ -1,14 +1,42 @@
 // src/renderer.rs
 
+use nalgebra_glm::{Mat4, Vec4};
 use crate::fragment::{CelestialType};
 use crate::framebuffer::Framebuffer;
-use crate::shaders::{fragment_shader, vertex_shader};
+use crate::shaders::{fragment_shader, screen_position, vertex_shader};
 use crate::triangle::triangle;
 use crate::Uniforms;
 use crate::Vertex;
 
+/// Modo de mezcla de los fragmentos con el framebuffer
+#[derive(Clone, Copy)]
+pub enum BlendMode {
+    Opaque,   // Reemplaza el color y escribe profundidad
+    Additive, // Suma el color sin escribir profundidad (atmósferas, brillos)
+    Alpha,    // Mezcla según la opacidad del fragmento, sin escribir profundidad (anillos)
+}
+
+/// Copia de una malla en la escena: su propia matriz de modelo y una semilla
+/// para que el shader varíe el aspecto de cada una
+#[derive(Clone, Copy)]
+pub struct Instance {
+    pub model_matrix: Mat4,
+    pub seed: u32,
+}
+
 /// Función principal de renderizado
 pub fn render(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], celestial_type: CelestialType) {
+    render_with_blend(framebuffer, uniforms, vertex_array, celestial_type, BlendMode::Opaque);
+}
+
+/// Renderizado con un modo de mezcla explícito
+pub fn render_with_blend(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    vertex_array: &[Vertex],
+    celestial_type: CelestialType,
+    blend_mode: BlendMode,
+) {
     // Vertex Shader
     let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
     for vertex in vertex_array {
@@ -16,22 +44,31 @@ pub fn render(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array:
         transformed_vertices.push(transformed);
     }
 
-    // Ensamblado de primitivas (triángulos)
+    // Ensamblado de primitivas (triángulos). Los que cruzan el plano cercano (o el lejano) se
+    // recortan en espacio de recorte: un vértice detrás de la cámara daría al proyectarse
+    // cajas enormes al rasterizar
+    let in_depth_range = |vertex: &Vertex| vertex.clip_position.w > 0.0 && vertex.clip_position.z.abs() <= vertex.clip_position.w;
     let mut triangles = Vec::new();
     for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
+        if i + 2 >= transformed_vertices.len() {
+            break;
+        }
+        let corners = &transformed_vertices[i..i + 3];
+        if corners.iter().all(in_depth_range) {
+            triangles.push([corners[0].clone(), corners[1].clone(), corners[2].clone()]);
+            continue;
+        }
+        // El polígono recortado se divide en abanico
+        let polygon = clip_depth(corners, uniforms);
+        for k in 1..polygon.len().saturating_sub(1) {
+            triangles.push([polygon[0].clone(), polygon[k].clone(), polygon[k + 1].clone()]);
         }
     }
 
     // Rasterización
     let mut fragments = Vec::new();
     for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2], celestial_type));
+        fragments.extend(triangle(&tri[0], &tri[1], &tri[2], celestial_type, framebuffer.width, framebuffer.height));
     }
 
     // Procesamiento de Fragmentos
@@ -40,10 +77,155 @@ pub fn render(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array:
         let y = fragment.position.y as usize;
 
         if x < framebuffer.width && y < framebuffer.height {
-            let (shaded_color, emissive) = fragment_shader(&fragment, &uniforms);
-            let color = shaded_color.to_hex();
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y, fragment.depth, emissive);
+            // La profundidad logarítmica se calcula con la w del fragmento (ver `vertex_shader`)
+            let depth = uniforms.log_depth.map_or(fragment.depth, |log_depth| log_depth.depth(1.0 / fragment.depth));
+            let output = fragment_shader(&fragment, uniforms);
+            framebuffer.set_current_color(output.color.to_hex());
+            match blend_mode {
+                BlendMode::Opaque => framebuffer.point(x, y, depth, output.emission.to_hex()),
+                BlendMode::Additive => framebuffer.point_additive(x, y, depth, output.emission.to_hex()),
+                BlendMode::Alpha => framebuffer.point_alpha(x, y, depth, output.emission.to_hex(), output.alpha),
+            }
+        }
+    }
+}
+
+// Recorta un polígono contra los planos z = w y z = -w del espacio de recorte, que son el
+// plano cercano y el lejano en cualquiera de las convenciones de profundidad
+fn clip_depth(polygon: &[Vertex], uniforms: &Uniforms) -> Vec<Vertex> {
+    let mut polygon = polygon.to_vec();
+    for sign in [1.0, -1.0] {
+        let distance = |vertex: &Vertex| vertex.clip_position.w - sign * vertex.clip_position.z;
+        let mut clipped = Vec::with_capacity(polygon.len() + 1);
+        for (i, current) in polygon.iter().enumerate() {
+            let next = &polygon[(i + 1) % polygon.len()];
+            let (d0, d1) = (distance(current), distance(next));
+            if d0 >= 0.0 {
+                clipped.push(current.clone());
+            }
+            if (d0 >= 0.0) != (d1 >= 0.0) {
+                clipped.push(clip_vertex(current, next, d0 / (d0 - d1), uniforms));
+            }
+        }
+        polygon = clipped;
+    }
+    polygon
+}
+
+// Vértice en la arista de `a` a `b`: los atributos se interpolan en espacio de recorte, donde
+// son lineales, y la posición en pantalla se vuelve a proyectar
+fn clip_vertex(a: &Vertex, b: &Vertex, t: f32, uniforms: &Uniforms) -> Vertex {
+    let clip_position = a.clip_position.lerp(&b.clip_position, t);
+    Vertex {
+        position: a.position.lerp(&b.position, t),
+        normal: a.normal.lerp(&b.normal, t),
+        tex_coords: a.tex_coords.lerp(&b.tex_coords, t),
+        color: a.color.lerp(&b.color, t),
+        transformed_position: screen_position(&clip_position, uniforms),
+        transformed_normal: a.transformed_normal.lerp(&b.transformed_normal, t).normalize(),
+        clip_position,
+    }
+}
+
+/// Renderiza la misma malla una vez por instancia, reutilizando los uniforms y
+/// descartando las instancias fuera del campo de visión
+pub fn render_instanced(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    vertex_array: &[Vertex],
+    instances: &[Instance],
+    celestial_type: CelestialType,
+) {
+    let mesh_radius = vertex_array.iter().map(|v| v.position.magnitude()).fold(0.0, f32::max);
+    let view_projection = uniforms.projection_matrix * uniforms.view_matrix;
+    let mut instance_uniforms = uniforms.clone();
+
+    for instance in instances {
+        // Esfera envolvente de la instancia contra el frustum en espacio de recorte
+        let scale = instance.model_matrix.fixed_view::<3, 3>(0, 0).column(0).magnitude();
+        let radius = mesh_radius * scale;
+        let center = view_projection * instance.model_matrix * Vec4::new(0.0, 0.0, 0.0, 1.0);
+        // En ortográfica w vale siempre 1 y no hay nada detrás de la cámara que descartar
+        let perspective = uniforms.projection_matrix[(3, 3)] == 0.0;
+        if perspective && center.w - radius <= 0.0 {
+            continue; // Detrás de la cámara o atravesando el plano cercano
+        }
+        // Distancia a los planos laterales: en perspectiva el plano x = w tiene normal
+        // (P00, 0, 1) en espacio de vista, así que se escala por su longitud
+        let side_margin = |scale: f32| if perspective { radius * (scale * scale + 1.0).sqrt() } else { radius * scale.abs() };
+        let margin_x = side_margin(uniforms.projection_matrix[(0, 0)]);
+        let margin_y = side_margin(uniforms.projection_matrix[(1, 1)]);
+        if center.x.abs() - margin_x > center.w || center.y.abs() - margin_y > center.w {
+            continue;
+        }
+
+        instance_uniforms.model_matrix = instance.model_matrix;
+        instance_uniforms.instance_seed = instance.seed;
+        render_with_blend(framebuffer, &instance_uniforms, vertex_array, celestial_type, BlendMode::Opaque);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fastnoise_lite::FastNoiseLite;
+    use nalgebra_glm::{Vec2, Vec3};
+    use std::sync::Arc;
+    use crate::color::Color;
+    use crate::projection::Projection;
+
+    fn clip_uniforms() -> Uniforms {
+        Uniforms::new(
+            Mat4::identity(),
+            Mat4::identity(),
+            Projection::new().matrix(1.0),
+            Mat4::identity(),
+            0.0,
+            Arc::new(FastNoiseLite::new()),
+            Vec3::new(0.0, 0.0, 1.0),
+            0.0, 0.0, Color::black(), 0.0, 0.0, 0.0,
+        )
+    }
+
+    fn clip_space_vertex(position: Vec3, uniforms: &Uniforms) -> Vertex {
+        vertex_shader(&Vertex::new(position, Vec3::new(0.0, 0.0, 1.0), Vec2::new(position.x, position.y)), uniforms)
+    }
+
+    #[test]
+    fn triangle_crossing_the_near_plane_is_clipped() {
+        // Un vértice detrás de la cámara (mira hacia -z) y dos delante
+        let uniforms = clip_uniforms();
+        let near = Projection::new().near;
+        let corners = [
+            clip_space_vertex(Vec3::new(0.0, 1.0, 2.0), &uniforms),
+            clip_space_vertex(Vec3::new(-1.0, 0.0, -5.0), &uniforms),
+            clip_space_vertex(Vec3::new(1.0, 0.0, -5.0), &uniforms),
+        ];
+
+        let polygon = clip_depth(&corners, &uniforms);
+        assert_eq!(polygon.len(), 4);
+        for vertex in &polygon {
+            assert!(vertex.clip_position.w >= near - 1e-5, "vértice detrás del plano cercano: {:?}", vertex.clip_position);
+            assert!(vertex.clip_position.z.abs() <= vertex.clip_position.w + 1e-5);
         }
+
+        // Los vértices nuevos caen sobre el plano cercano, con los atributos interpolados
+        let new_vertices: Vec<&Vertex> = polygon.iter().filter(|vertex| (vertex.clip_position.w - near).abs() < 1e-5).collect();
+        assert_eq!(new_vertices.len(), 2);
+        for vertex in new_vertices {
+            assert!((vertex.position.z + near).abs() < 1e-4);
+            assert!((vertex.tex_coords.y - vertex.position.y).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn triangle_behind_the_camera_is_discarded() {
+        let uniforms = clip_uniforms();
+        let corners = [
+            clip_space_vertex(Vec3::new(0.0, 1.0, 2.0), &uniforms),
+            clip_space_vertex(Vec3::new(-1.0, 0.0, 3.0), &uniforms),
+            clip_space_vertex(Vec3::new(1.0, 0.0, 3.0), &uniforms),
+        ];
+        assert!(clip_depth(&corners, &uniforms).is_empty());
     }
 }
@@ -0,0 +1,185 @@
+// src/rings.rs
+
+use nalgebra_glm::Vec3;
+use std::f32::consts::TAU;
+use std::fs;
+use std::io;
+use crate::color::Color;
+use crate::fragment::{Fragment, FragmentOutput};
+use crate::random::Rng;
+use crate::shaders::{body_light_direction, smoothstep};
+use crate::shadows::body_shadow;
+use crate::Uniforms;
+
+/// Muestra del perfil radial. `radius` va de 0 (borde interior) a 1 (borde exterior)
+#[derive(Clone, Copy)]
+pub struct RingSample {
+    pub radius: f32,
+    pub density: f32,
+    pub color: Color,
+}
+
+/// División entre anillos (como la de Cassini)
+#[derive(Clone)]
+pub struct RingGap {
+    pub name: String,
+    pub center: f32,  // Posición radial normalizada, en [0, 1]
+    pub width: f32,
+    pub depth: f32,   // 1.0 = vacía por completo
+}
+
+/// Perfil radial de densidad y color de un sistema de anillos
+pub struct RingProfile {
+    pub samples: Vec<RingSample>,
+    pub gaps: Vec<RingGap>,
+}
+
+impl RingProfile {
+    /// Perfil procedural: anillos finos aleatorios alrededor de `base_color`
+    pub fn procedural(seed: u64, base_color: Color, gaps: Vec<RingGap>) -> Self {
+        let mut rng = Rng::new(seed);
+        let count = 256;
+        // Anillos oscuros del mismo tono, con menos luminosidad
+        let (hue, saturation, lightness) = base_color.to_hsl();
+        let dark = Color::from_hsl(hue, saturation, lightness * 0.55);
+
+        // Paseo aleatorio suavizado para que los anillos vecinos se parezcan
+        let mut density: f32 = 0.5;
+        let samples = (0..count)
+            .map(|i| {
+                let radius = i as f32 / (count - 1) as f32;
+                density = (density + rng.range(-0.12, 0.12)).clamp(0.15, 1.0);
+                let envelope = smoothstep(0.0, 0.08, radius) * smoothstep(1.0, 0.92, radius);
+                RingSample { radius, density: density * envelope, color: dark.lerp(&base_color, density) }
+            })
+            .collect();
+
+        RingProfile { samples, gaps }
+    }
+
+    /// Carga un perfil desde un archivo de texto con líneas
+    /// `sample <radio> <densidad> <r> <g> <b>` y `gap <nombre> <centro> <ancho> <profundidad>`
+    pub fn load(path: &str) -> io::Result<Self> {
+        let invalid = |line: &str| io::Error::new(io::ErrorKind::InvalidData, format!("línea inválida en el perfil de anillos: {}", line));
+        let mut samples = Vec::new();
+        let mut gaps = Vec::new();
+
+        for line in fs::read_to_string(path)?.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["sample", values @ ..] if values.len() == 5 => {
+                    let values: Vec<f32> = values.iter().map(|v| v.parse()).collect::<Result<_, _>>().map_err(|_| invalid(line))?;
+                    let channel = |value: f32| value.clamp(0.0, 255.0) as u8;
+                    samples.push(RingSample {
+                        radius: values[0],
+                        density: values[1],
+                        color: Color::new(channel(values[2]), channel(values[3]), channel(values[4])),
+                    });
+                }
+                ["gap", name, values @ ..] if values.len() == 3 => {
+                    let values: Vec<f32> = values.iter().map(|v| v.parse()).collect::<Result<_, _>>().map_err(|_| invalid(line))?;
+                    gaps.push(RingGap { name: name.to_string(), center: values[0], width: values[1], depth: values[2] });
+                }
+                _ => return Err(invalid(line)),
+            }
+        }
+
+        if samples.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "el perfil de anillos no tiene muestras"));
+        }
+        samples.sort_by(|a, b| a.radius.total_cmp(&b.radius));
+        Ok(RingProfile { samples, gaps })
+    }
+
+    /// Densidad y color a una posición radial normalizada
+    pub fn sample(&self, radius: f32) -> (f32, Color) {
+        if !(0.0..=1.0).contains(&radius) {
+            return (0.0, Color::black());
+        }
+
+        let next = self.samples.partition_point(|s| s.radius < radius);
+        let (density, color) = if next == 0 {
+            (self.samples[0].density, self.samples[0].color)
+        } else if next == self.samples.len() {
+            let last = self.samples[next - 1];
+            (last.density, last.color)
+        } else {
+            let (a, b) = (self.samples[next - 1], self.samples[next]);
+            let t = (radius - a.radius) / (b.radius - a.radius).max(1e-6);
+            (a.density + (b.density - a.density) * t, a.color.lerp(&b.color, t))
+        };
+
+        let gap_factor: f32 = self
+            .gaps
+            .iter()
+            .map(|gap| {
+                let half = gap.width * 0.5;
+                1.0 - gap.depth * smoothstep(half, half * 0.6, (radius - gap.center).abs())
+            })
+            .product();
+
+        (density * gap_factor, color)
+    }
+}
+
+// Perfil en una posición radial normalizada, con ondas de densidad
+fn ring_density(uniforms: &Uniforms, profile: &RingProfile, radius: f32) -> (f32, Color) {
+    let (density, color) = profile.sample(radius);
+
+    let waves = 0.9 + 0.1 * (radius * uniforms.ring_frequency * TAU - uniforms.time * uniforms.ring_wave_speed).sin();
+    (density * waves, color)
+}
+
+/// Luz que atraviesa los anillos hasta un punto del planeta (espacio del cuerpo).
+/// 1.0 = sin sombra
+pub fn ring_shadow(uniforms: &Uniforms, position: &Vec3, light_dir: &Vec3) -> f32 {
+    let Some(profile) = &uniforms.ring_profile else {
+        return 1.0;
+    };
+
+    let normal = uniforms.ring_normal;
+    let facing = normal.dot(light_dir);
+    if facing.abs() < 1e-4 {
+        return 1.0;
+    }
+    let t = -normal.dot(position) / facing;
+    if t <= 0.0 {
+        return 1.0;
+    }
+
+    let hit = position + light_dir * t;
+    let span = uniforms.ring_outer_radius - uniforms.ring_inner_radius;
+    let radius = (hit.magnitude() - uniforms.ring_inner_radius) / span;
+    let (density, _) = ring_density(uniforms, profile, radius);
+    1.0 - (density * uniforms.ring_opacity).clamp(0.0, 1.0)
+}
+
+/// Shader de los anillos: color y opacidad desde el perfil radial, con la sombra del planeta
+pub fn rings_shader(fragment: &Fragment, uniforms: &Uniforms) -> FragmentOutput {
+    let Some(profile) = &uniforms.ring_profile else {
+        return FragmentOutput::surface(Color::black()).with_alpha(0.0);
+    };
+
+    // La malla es un anillo plano: u es la posición radial y la normal es +Y
+    let (density, color) = ring_density(uniforms, profile, fragment.tex_coords.x);
+    let alpha = (density * uniforms.ring_opacity).clamp(0.0, 1.0);
+    if alpha <= 0.0 {
+        return FragmentOutput::surface(Color::black()).with_alpha(0.0);
+    }
+
+    // La sombra del planeta llega del mapa de sombras (y de los eclipses) en `body_shadow`
+    let light_dir = body_light_direction(uniforms);
+
+    // Las partículas se iluminan por ambas caras, algo menos por la cara oscura
+    let direct = (0.55 + 0.45 * light_dir.y.abs()) * body_shadow(fragment, uniforms);
+    let base = color.blend_multiply(&uniforms.ring_color);
+    let lit = base.blend_multiply(&uniforms.light_color) * direct
+        + base.blend_multiply(&uniforms.ambient_color) * uniforms.ambient_intensity;
+
+    FragmentOutput::surface(lit).with_alpha(alpha)
+}
@@ -0,0 +1,125 @@
+// src/shadow_map.rs
+
+use nalgebra_glm::{self as glm, Mat4, Vec3, Vec4};
+use crate::fragment::CelestialType;
+use crate::shaders::vertex_shader;
+use crate::triangle::triangle;
+use crate::vertex::Vertex;
+use crate::Uniforms;
+
+/// Mapa de sombras de una luz direccional: profundidad vista desde la luz con una
+/// proyección ortográfica que cubre una esfera de la escena alrededor del cuerpo.
+/// Se crea una vez y `update` lo mantiene al día entre cuadros
+#[derive(Clone)]
+pub struct ShadowMap {
+    pub size: usize,
+    pub depth: Vec<f32>,
+    pub view_matrix: Mat4,
+    pub projection_matrix: Mat4,
+    pub viewport_matrix: Mat4,
+    pub bias: f32,        // Margen de profundidad contra el acné de sombras (NDC)
+    pub pcf_radius: i32,  // Texeles vecinos a cada lado que se promedian
+    radius: f32,
+    light_direction: Vec3,
+    rendered_model: Option<Mat4>, // Matriz de modelo con la que se rasterizó la profundidad
+}
+
+impl ShadowMap {
+    /// Mapa de `size` x `size` que cubre una esfera de radio `radius` alrededor del cuerpo
+    pub fn new(size: usize, radius: f32) -> Self {
+        let half = size as f32 / 2.0;
+        ShadowMap {
+            size,
+            depth: vec![f32::INFINITY; size * size],
+            view_matrix: Mat4::identity(),
+            projection_matrix: glm::ortho(-radius, radius, -radius, radius, radius * 0.5, radius * 4.0),
+            viewport_matrix: Mat4::new(
+                half, 0.0, 0.0, half,
+                0.0, -half, 0.0, half,
+                0.0, 0.0, 1.0, 0.0,
+                0.0, 0.0, 0.0, 1.0,
+            ),
+            bias: 0.004,
+            pcf_radius: 1,
+            radius,
+            light_direction: Vec3::zeros(),
+            rendered_model: None,
+        }
+    }
+
+    /// Orienta el mapa según `light_direction` (hacia la luz), centrado en el cuerpo de
+    /// `uniforms`, y vuelve a rasterizar la malla solo si la luz ha cambiado o el giro o la
+    /// escala del modelo mueven la malla más de medio texel. Si el cuerpo solo se traslada
+    /// basta con mover la vista de la luz
+    pub fn update(&mut self, light_direction: Vec3, uniforms: &Uniforms, vertex_array: &[Vertex]) {
+        let light_dir = light_direction.normalize();
+        let center = uniforms.model_matrix.column(3).xyz();
+        let up = if light_dir.y.abs() > 0.99 { Vec3::new(1.0, 0.0, 0.0) } else { Vec3::new(0.0, 1.0, 0.0) };
+        let eye = center + light_dir * (self.radius * 2.0);
+        self.view_matrix = glm::look_at(&eye, &center, &up);
+
+        let stale = match &self.rendered_model {
+            Some(model) => light_dir != self.light_direction || self.drift(model, &uniforms.model_matrix) > self.radius / self.size as f32,
+            None => true,
+        };
+        if stale {
+            self.light_direction = light_dir;
+            self.depth.fill(f32::INFINITY);
+            self.render(uniforms, vertex_array);
+            self.rendered_model = Some(uniforms.model_matrix);
+        }
+    }
+
+    // Cota del desplazamiento de la malla entre dos matrices de modelo, sin contar la traslación
+    fn drift(&self, from: &Mat4, to: &Mat4) -> f32 {
+        let scale = to.fixed_view::<3, 3>(0, 0).column(0).magnitude().max(1e-6);
+        (to.fixed_view::<3, 3>(0, 0) - from.fixed_view::<3, 3>(0, 0)).norm() * self.radius / scale
+    }
+
+    // Rasteriza una malla solo en profundidad desde la luz. Se usan los uniforms del
+    // cuerpo (matriz de modelo, desplazamiento) con las matrices de la luz
+    fn render(&mut self, uniforms: &Uniforms, vertex_array: &[Vertex]) {
+        let mut light_uniforms = uniforms.clone();
+        light_uniforms.view_matrix = self.view_matrix;
+        light_uniforms.projection_matrix = self.projection_matrix;
+        light_uniforms.viewport_matrix = self.viewport_matrix;
+        light_uniforms.log_depth = None; // El mapa guarda la profundidad lineal de la luz
+
+        let transformed: Vec<Vertex> = vertex_array.iter().map(|v| vertex_shader(v, &light_uniforms)).collect();
+        for tri in transformed.chunks_exact(3) {
+            // El tipo de cuerpo no importa: solo se usa la profundidad del fragmento
+            for fragment in triangle(&tri[0], &tri[1], &tri[2], CelestialType::Rocky, self.size, self.size) {
+                let (x, y) = (fragment.position.x as usize, fragment.position.y as usize);
+                if x < self.size && y < self.size {
+                    let index = y * self.size + x;
+                    self.depth[index] = self.depth[index].min(fragment.depth);
+                }
+            }
+        }
+    }
+
+    /// Fracción de luz en un punto del mundo (1.0 = iluminado), con filtrado PCF
+    pub fn visibility(&self, world_position: &Vec3) -> f32 {
+        let light_space = self.viewport_matrix
+            * self.projection_matrix
+            * self.view_matrix
+            * Vec4::new(world_position.x, world_position.y, world_position.z, 1.0);
+        let (x, y) = (light_space.x.floor() as i32, light_space.y.floor() as i32);
+        let depth = light_space.z - self.bias;
+
+        let mut lit = 0.0;
+        let mut samples = 0.0;
+        for dy in -self.pcf_radius..=self.pcf_radius {
+            for dx in -self.pcf_radius..=self.pcf_radius {
+                let (sx, sy) = (x + dx, y + dy);
+                samples += 1.0;
+                // Fuera del mapa no hay nada que haga sombra
+                let outside = sx < 0 || sy < 0 || sx >= self.size as i32 || sy >= self.size as i32;
+                if outside || depth <= self.depth[sy as usize * self.size + sx as usize] {
+                    lit += 1.0;
+                }
+            }
+        }
+        lit / samples
+    }
+}
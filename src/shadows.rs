@@ -0,0 +1,64 @@
+// src/shadows.rs
+
+use nalgebra_glm::Vec3;
+use crate::fragment::Fragment;
+use crate::shaders::smoothstep;
+use crate::Uniforms;
+
+/// Esfera que puede tapar la luz de la estrella, en espacio del mundo
+#[derive(Clone, Copy)]
+pub struct Occluder {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+/// Dirección hacia la luz desde un punto del mundo (luz puntual o direccional)
+pub fn light_direction_at(uniforms: &Uniforms, world_position: &Vec3) -> Vec3 {
+    match uniforms.light_position {
+        Some(light_position) => (light_position - world_position).normalize(),
+        None => uniforms.light_direction.normalize(),
+    }
+}
+
+/// Fracción de la luz de la estrella que llega a `world_position` (1.0 = sin eclipse).
+/// Se compara el disco de la estrella con el de cada ocultador vistos desde el punto:
+/// dentro de la umbra no llega luz y en la penumbra se tapa solo una parte del disco
+pub fn eclipse_factor(uniforms: &Uniforms, world_position: &Vec3) -> f32 {
+    let light_dir = light_direction_at(uniforms, world_position);
+    let light_radius = uniforms.light_angular_radius.max(1e-4);
+
+    uniforms
+        .occluders
+        .iter()
+        .map(|occluder| {
+            let to_occluder = occluder.center - world_position;
+            let distance = to_occluder.magnitude();
+            if distance <= occluder.radius || to_occluder.dot(&light_dir) <= 0.0 {
+                return 1.0; // Dentro del ocultador o detrás del punto respecto a la luz
+            }
+
+            let occluder_radius = (occluder.radius / distance).asin();
+            let separation = (to_occluder / distance).dot(&light_dir).clamp(-1.0, 1.0).acos();
+            let (a, b) = (occluder_radius, light_radius);
+            if separation >= a + b {
+                return 1.0;
+            }
+
+            // Fracción máxima del disco cubierta: total si el ocultador es mayor (eclipse total),
+            // o la razón de áreas si es menor (eclipse anular)
+            let full = (a / b).powi(2).min(1.0);
+            let overlap = ((a + b - separation) / (2.0 * a.min(b))).clamp(0.0, 1.0);
+            1.0 - full * smoothstep(0.0, 1.0, overlap)
+        })
+        .product()
+}
+
+/// Sombra sobre un fragmento: eclipses analíticos de otros cuerpos y, si hay, el mapa de sombras
+pub fn body_shadow(fragment: &Fragment, uniforms: &Uniforms) -> f32 {
+    if uniforms.occluders.is_empty() && uniforms.shadow_map.is_none() {
+        return 1.0;
+    }
+    let world_position = (uniforms.model_matrix * fragment.vertex_position).xyz();
+    let shadow_map = uniforms.shadow_map.as_ref().map_or(1.0, |map| map.visibility(&world_position));
+    eclipse_factor(uniforms, &world_position) * shadow_map
+}
@@ -0,0 +1,195 @@
+// src/skybox.rs
+
+use nalgebra_glm::Vec3;
+use std::sync::Arc;
+use fastnoise_lite::FastNoiseLite;
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+use crate::random::Rng;
+use crate::shaders::smoothstep;
+
+const NEBULA_BLOCK: usize = 2; // La nebulosa se evalúa en bloques de 2x2 píxeles
+
+/// Estrella de fondo, en el infinito
+pub struct SkyStar {
+    pub direction: Vec3,
+    pub magnitude: f32, // Magnitud aparente: menor = más brillante
+    pub color: Color,
+}
+
+/// Fondo procedural: campo de estrellas y banda galáctica que solo dependen de la
+/// orientación de la cámara, no de su posición
+pub struct Skybox {
+    pub stars: Vec<SkyStar>,
+    pub nebula: Arc<FastNoiseLite>,
+    pub galactic_normal: Vec3,  // Normal del plano de la galaxia
+    pub galactic_center: Vec3,  // Dirección del núcleo galáctico, dentro del plano
+    pub band_width: f32,        // Semiancho de la banda, en radianes
+    pub nebula_intensity: f32,
+    cache: Vec<u32>,
+    cached_view: Option<SkyView>,
+}
+
+// Base de la cámara y semiancho/semialto del campo de visión a distancia 1
+#[derive(Clone, Copy, PartialEq)]
+struct SkyView {
+    right: Vec3,
+    up: Vec3,
+    forward: Vec3,
+    half_width: f32,
+    half_height: f32,
+}
+
+impl Skybox {
+    pub fn new(seed: u64, star_count: usize, nebula: Arc<FastNoiseLite>) -> Self {
+        let mut rng = Rng::new(seed);
+        let galactic_normal = Vec3::new(0.35, 0.85, 0.4).normalize();
+        let galactic_center = galactic_normal.cross(&Vec3::new(0.0, 0.0, 1.0)).normalize();
+
+        let stars = (0..star_count)
+            .map(|_| {
+                // La mitad de las estrellas se concentra cerca del plano galáctico
+                let mut direction = rng.unit_vector();
+                if rng.next_f32() < 0.5 {
+                    direction -= galactic_normal * (galactic_normal.dot(&direction) * 0.85);
+                    direction = direction.normalize();
+                }
+
+                // Hay muchas más estrellas débiles que brillantes
+                let magnitude = 6.5 - 7.5 * rng.next_f32().powi(3);
+                // A simple vista las estrellas lejanas se ven casi blancas: se conserva el tono
+                // del cuerpo negro con la mitad de saturación
+                let temperature = 2800.0 + 9000.0 * rng.next_f32().powi(2);
+                let (hue, saturation, value) = Color::from_temperature(temperature).to_hsv();
+                SkyStar { direction, magnitude, color: Color::from_hsv(hue, saturation * 0.5, value) }
+            })
+            .collect();
+
+        Skybox {
+            stars,
+            nebula,
+            galactic_normal,
+            galactic_center,
+            band_width: 0.22,
+            nebula_intensity: 0.35,
+            cache: Vec::new(),
+            cached_view: None,
+        }
+    }
+
+    /// Dibuja el fondo en el buffer de color. Debe llamarse justo después de `clear`;
+    /// no escribe en el z-buffer, así que cualquier cuerpo queda por delante.
+    /// Los rayos salen de la base de la cámara con su campo de visión, sea cual sea la
+    /// proyección: en ortográfica el fondo se sigue viendo en perspectiva
+    pub fn render(&mut self, framebuffer: &mut Framebuffer, camera: &Camera) {
+        // Solo cuenta la orientación de la cámara: el fondo está en el infinito
+        let aspect_ratio = framebuffer.width as f32 / framebuffer.height as f32;
+        let view = SkyView {
+            right: camera.basis_change(&Vec3::new(1.0, 0.0, 0.0)),
+            up: camera.basis_change(&Vec3::new(0.0, 1.0, 0.0)),
+            forward: camera.forward(),
+            half_width: (camera.projection.fov * 0.5).tan() * aspect_ratio,
+            half_height: (camera.projection.fov * 0.5).tan(),
+        };
+
+        // La nebulosa es cara: se recalcula solo cuando cambia la orientación
+        let size = framebuffer.width * framebuffer.height;
+        if self.cached_view != Some(view) || self.cache.len() != size {
+            self.cache = self.render_nebula(framebuffer.width, framebuffer.height, &view);
+            self.cached_view = Some(view);
+        }
+        framebuffer.buffer.copy_from_slice(&self.cache);
+
+        for star in &self.stars {
+            let depth = star.direction.dot(&view.forward);
+            if depth <= 0.0 {
+                continue; // Detrás de la cámara
+            }
+            let ndc_x = star.direction.dot(&view.right) / (depth * view.half_width);
+            let ndc_y = star.direction.dot(&view.up) / (depth * view.half_height);
+            let screen_x = (ndc_x + 1.0) * 0.5 * framebuffer.width as f32;
+            let screen_y = (1.0 - ndc_y) * 0.5 * framebuffer.height as f32;
+            if screen_x < 0.0 || screen_y < 0.0 {
+                continue;
+            }
+
+            // Brillo relativo: cada magnitud es un factor 2.512
+            let brightness = 2.512_f32.powf(-star.magnitude).min(1.6);
+            let color = star.color * brightness.min(1.0);
+            let (x, y) = (screen_x as usize, screen_y as usize);
+            add_pixel(framebuffer, x, y, color);
+
+            // Las más brillantes se ven algo más grandes
+            if brightness > 0.4 {
+                let halo = star.color * (brightness * 0.35).min(0.6);
+                add_pixel(framebuffer, x + 1, y, halo);
+                add_pixel(framebuffer, x, y + 1, halo);
+                if x > 0 {
+                    add_pixel(framebuffer, x - 1, y, halo);
+                }
+                if y > 0 {
+                    add_pixel(framebuffer, x, y - 1, halo);
+                }
+            }
+        }
+    }
+
+    // Banda galáctica por píxel, a partir de la dirección de visión
+    fn render_nebula(&self, width: usize, height: usize, view: &SkyView) -> Vec<u32> {
+        let mut pixels = vec![0; width * height];
+
+        for block_y in (0..height).step_by(NEBULA_BLOCK) {
+            for block_x in (0..width).step_by(NEBULA_BLOCK) {
+                let sx = block_x as f32 + NEBULA_BLOCK as f32 * 0.5;
+                let sy = block_y as f32 + NEBULA_BLOCK as f32 * 0.5;
+                let ndc_x = 2.0 * sx / width as f32 - 1.0;
+                let ndc_y = 1.0 - 2.0 * sy / height as f32;
+                let direction = (view.forward + view.right * (ndc_x * view.half_width) + view.up * (ndc_y * view.half_height)).normalize();
+                let color = self.nebula_color(&direction).to_hex();
+
+                for y in block_y..(block_y + NEBULA_BLOCK).min(height) {
+                    for x in block_x..(block_x + NEBULA_BLOCK).min(width) {
+                        pixels[y * width + x] = color;
+                    }
+                }
+            }
+        }
+        pixels
+    }
+
+    /// Color de la nebulosa en una dirección del mundo
+    pub fn nebula_color(&self, direction: &Vec3) -> Color {
+        // Latitud galáctica: la banda se ensancha hacia el núcleo
+        let latitude = self.galactic_normal.dot(direction).clamp(-1.0, 1.0).asin();
+        let toward_center = (self.galactic_center.dot(direction) + 1.0) * 0.5;
+        let width = self.band_width * (0.7 + 0.6 * toward_center);
+        let band = (-(latitude / width).powi(2)).exp();
+        if band < 0.01 {
+            return Color::black();
+        }
+
+        // Nubes con ruido celular en dos escalas; los bordes de las celdas forman
+        // las franjas oscuras de polvo
+        let p = direction * 40.0;
+        let clouds = 1.0 - (self.nebula.get_noise_3d(p.x, p.y, p.z) + 1.0) * 0.5;
+        let q = direction * 110.0;
+        let detail = 1.0 - (self.nebula.get_noise_3d(q.x + 17.0, q.y, q.z) + 1.0) * 0.5;
+        let dust = smoothstep(0.55, 0.8, detail) * smoothstep(0.5, 0.0, latitude.abs() / width);
+        let density = (0.35 + 0.65 * clouds) * (0.8 + 0.4 * detail) * (1.0 - 0.7 * dust);
+
+        // Núcleo cálido y brazos azulados
+        let core = Color::new(255, 214, 160);
+        let arms = Color::new(130, 150, 230);
+        let tint = arms.lerp(&core, toward_center.powi(3));
+        tint * (band * density * self.nebula_intensity * (0.6 + 0.8 * toward_center.powi(4)))
+    }
+}
+
+// Suma un color al buffer de color, sin prueba de profundidad
+fn add_pixel(framebuffer: &mut Framebuffer, x: usize, y: usize, color: Color) {
+    if x < framebuffer.width && y < framebuffer.height {
+        let index = y * framebuffer.width + x;
+        framebuffer.buffer[index] = Color::from_hex(framebuffer.buffer[index]).blend_add(&color).to_hex();
+    }
+}
@@ -0,0 +1,199 @@
+// src/star.rs
+
+use nalgebra_glm::{Mat4, Vec2, Vec3, Vec4};
+use crate::color::Color;
+use crate::fragment::{Fragment, FragmentOutput};
+use crate::random::Rng;
+use crate::shaders::smoothstep;
+use crate::vertex::Vertex;
+use crate::Uniforms;
+
+/// Parámetros físicos y visuales de una estrella
+#[derive(Clone, Copy)]
+pub struct StarSettings {
+    pub temperature: f32,        // Temperatura efectiva en Kelvin
+    pub granulation_scale: f32,  // Tamaño de los gránulos (mayor = más pequeños)
+    pub granulation_speed: f32,  // Velocidad de evolución de la granulación
+    pub sunspot_scale: f32,
+    pub sunspot_coverage: f32,   // Fracción aproximada de la banda de manchas cubierta
+    pub limb_darkening: f32,     // Coeficiente lineal u: I(μ) = 1 - u(1 - μ)
+    pub corona_radius: f32,      // Radio exterior de la corona, en radios de la estrella
+    pub corona_intensity: f32,
+}
+
+// Estrella anaranjada de tipo K
+pub const SUN: StarSettings = StarSettings {
+    temperature: 3900.0,
+    granulation_scale: 14.0,
+    granulation_speed: 0.15,
+    sunspot_scale: 2.5,
+    sunspot_coverage: 0.18,
+    limb_darkening: 0.6,
+    corona_radius: 1.6,
+    corona_intensity: 0.7,
+};
+
+/// Protuberancia solar: un arco de plasma anclado en la superficie
+pub struct Prominence {
+    pub anchor: Vec3,  // Dirección del punto de anclaje, en espacio de la estrella
+    pub height: f32,   // Altura en radios de la estrella
+    pub width: f32,    // Anchura del arco en radios de la estrella
+    pub phase: f32,    // Desfase de la animación
+}
+
+/// Genera protuberancias en posiciones aleatorias reproducibles
+pub fn generate_prominences(seed: u64, count: usize) -> Vec<Prominence> {
+    let mut rng = Rng::new(seed);
+    (0..count)
+        .map(|_| Prominence {
+            anchor: rng.unit_vector(),
+            height: rng.range(0.12, 0.3),
+            width: rng.range(0.1, 0.22),
+            phase: rng.range(0.0, std::f32::consts::TAU),
+        })
+        .collect()
+}
+
+/// Construye un billboard (dos triángulos orientados a la cámara) por protuberancia.
+/// Los vértices quedan en espacio del mundo; `star_radius` es el radio de la malla
+pub fn prominence_billboards(
+    prominences: &[Prominence],
+    model_matrix: &Mat4,
+    star_radius: f32,
+    camera_position: Vec3,
+    time: f32,
+) -> Vec<Vertex> {
+    let scale = model_matrix.fixed_view::<3, 3>(0, 0).column(0).magnitude() * star_radius;
+    let mut vertices = Vec::with_capacity(prominences.len() * 6);
+
+    for prominence in prominences {
+        let anchor = model_matrix * Vec4::new(
+            prominence.anchor.x * star_radius,
+            prominence.anchor.y * star_radius,
+            prominence.anchor.z * star_radius,
+            1.0,
+        );
+        let anchor = anchor.xyz();
+        let outward = (model_matrix.fixed_view::<3, 3>(0, 0) * prominence.anchor).normalize();
+        let to_camera = (camera_position - anchor).normalize();
+
+        // El arco se levanta en la dirección de la normal, girado hacia la cámara
+        let right = outward.cross(&to_camera);
+        if right.magnitude() < 1e-3 {
+            continue;
+        }
+        let right = right.normalize();
+        let up = to_camera.cross(&right).normalize();
+
+        let pulse = 0.85 + 0.15 * (time * 0.6 + prominence.phase).sin();
+        let half_width = prominence.width * scale * 0.5;
+        let height = prominence.height * scale * pulse;
+
+        let corner = |u: f32, v: f32| {
+            let position = anchor + right * ((u - 0.5) * 2.0 * half_width) + up * (v * height);
+            Vertex::new(position, to_camera, Vec2::new(u, v))
+        };
+        let (bottom_left, bottom_right) = (corner(0.0, 0.0), corner(1.0, 0.0));
+        let (top_left, top_right) = (corner(0.0, 1.0), corner(1.0, 1.0));
+        vertices.extend([bottom_left.clone(), bottom_right, top_right.clone(), bottom_left, top_right, top_left]);
+    }
+
+    vertices
+}
+
+/// Fotosfera animada: granulación, manchas, oscurecimiento del limbo y color de cuerpo negro
+pub fn star_shader(fragment: &Fragment, uniforms: &Uniforms) -> FragmentOutput {
+    let settings = uniforms.star.unwrap_or(SUN);
+    let direction = fragment.vertex_position.xyz().normalize();
+    let t = uniforms.time * settings.granulation_speed;
+
+    // Granulación: ruido celular con dominio deformado que evoluciona con el tiempo
+    let scale = settings.granulation_scale;
+    let (wx, wy, wz) = uniforms.detail_noise.domain_warp_3d(
+        direction.x * scale + t,
+        direction.y * scale,
+        direction.z * scale - t,
+    );
+    let cells = uniforms.detail_noise.get_noise_3d(wx, wy, wz);
+    let granulation = 0.7 + 0.3 * smoothstep(0.0, 0.35, cells + 1.0);
+
+    // Manchas solares en bandas de latitud media, derivando lentamente
+    let drift = uniforms.time * 0.02;
+    let spot_noise = uniforms.noise.get_noise_3d(
+        direction.x * settings.sunspot_scale + drift,
+        direction.y * settings.sunspot_scale,
+        direction.z * settings.sunspot_scale,
+    );
+    let spot_noise = (spot_noise + 1.0) * 0.5;
+    let latitude = direction.y.abs();
+    let band = smoothstep(0.05, 0.15, latitude) * smoothstep(0.55, 0.4, latitude);
+    let threshold = 1.0 - settings.sunspot_coverage;
+    let penumbra = smoothstep(threshold, threshold + 0.05, spot_noise) * band;
+    let umbra = smoothstep(threshold + 0.08, threshold + 0.12, spot_noise) * band;
+    let spots = 1.0 - 0.35 * penumbra - 0.45 * umbra;
+
+    // Oscurecimiento del limbo según el ángulo de visión; el borde también es más frío
+    // (se usa la dirección radial y no la normal de la malla, que es irregular)
+    let world_position = (uniforms.model_matrix * fragment.vertex_position).xyz();
+    let center = uniforms.model_matrix.column(3).xyz();
+    let radial = (world_position - center).normalize();
+    let to_camera = (uniforms.camera_position - world_position).normalize();
+    let mu = radial.dot(&to_camera).clamp(0.0, 1.0);
+    let limb = 1.0 - settings.limb_darkening * (1.0 - mu);
+    let temperature = settings.temperature * (0.85 + 0.15 * mu);
+
+    // La emisión se suma otra vez en el post-procesamiento, así que se deja margen
+    let color = Color::from_temperature(temperature) * (0.6 * granulation * spots * limb);
+    FragmentOutput::emissive(color)
+}
+
+/// Corona: capa exterior aditiva que solo aporta al buffer emisivo
+pub fn corona_shader(fragment: &Fragment, uniforms: &Uniforms) -> FragmentOutput {
+    let settings = uniforms.star.unwrap_or(SUN);
+
+    // Cámara y fragmento en radios de la estrella
+    let inverse_model = uniforms.model_matrix.try_inverse().unwrap_or_default();
+    let eye = inverse_model * Vec4::new(uniforms.camera_position.x, uniforms.camera_position.y, uniforms.camera_position.z, 1.0);
+    let eye = eye.xyz() * settings.corona_radius;
+    let position = fragment.vertex_position.xyz().normalize() * settings.corona_radius;
+
+    let facing = position.dot(&(eye - position)) > 0.0;
+    let inside = eye.magnitude() < settings.corona_radius;
+    if facing == inside {
+        return FragmentOutput::surface(Color::black());
+    }
+
+    // Distancia mínima del rayo de visión al centro de la estrella
+    let direction = (position - eye).normalize();
+    let closest = eye - direction * eye.dot(&direction);
+    // Sobre el disco la corona se desvanece (la malla de la estrella es irregular)
+    let impact = closest.magnitude();
+    let over_disc = smoothstep(0.8, 1.0, impact);
+    let falloff = ((settings.corona_radius - impact.max(1.0)) / (settings.corona_radius - 1.0)).clamp(0.0, 1.0);
+
+    // Serpentinas radiales que cambian lentamente
+    let around = closest.normalize();
+    let streamers = uniforms.noise.get_noise_3d(
+        around.x * 4.0,
+        around.y * 4.0,
+        around.z * 4.0 + uniforms.time * 0.05,
+    );
+    let streamers = 0.6 + 0.4 * streamers;
+
+    let glow = falloff.powf(2.5) * over_disc * streamers * settings.corona_intensity;
+    FragmentOutput::new(Color::black(), Color::from_temperature(settings.temperature) * glow)
+}
+
+/// Protuberancia: un arco brillante dibujado con las coordenadas UV del billboard
+pub fn prominence_shader(fragment: &Fragment, uniforms: &Uniforms) -> FragmentOutput {
+    let uv = fragment.tex_coords;
+    let arc_distance = (Vec2::new((uv.x - 0.5) / 0.4, uv.y / 0.85).magnitude() - 1.0).abs();
+    let arc = (-(arc_distance / 0.12).powi(2)).exp();
+
+    // Parpadeo a lo largo del arco
+    let flicker = uniforms.noise.get_noise_2d(uv.x * 12.0, uv.y * 12.0 + uniforms.time * 2.0);
+    let intensity = arc * (0.7 + 0.3 * flicker) * (1.0 - uv.y * 0.4);
+
+    let plasma_color = Color::from_hsv(12.0, 0.73, 1.0); // Rojo de hidrógeno (H-alfa)
+    FragmentOutput::new(Color::black(), plasma_color * intensity)
+}
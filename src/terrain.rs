@@ -0,0 +1,357 @@
+// src/terrain.rs
+
+use nalgebra_glm::{Vec3, dot};
+use crate::color::Color;
+use crate::fragment::{Fragment, FragmentOutput};
+use crate::atmosphere::{self, AtmosphereSettings};
+use crate::clouds::CloudLayer;
+use crate::shaders::{apply_lighting, apply_lighting_with_shadow, body_light_direction, smoothstep};
+use crate::Uniforms;
+
+/// Colores de cada tipo de terreno de un planeta rocoso
+pub struct BiomePalette {
+    pub ocean: Color,
+    pub mountain: Color,
+    pub continent: Color,
+    pub snow: Color,
+}
+
+impl BiomePalette {
+    /// Color de las cumbres: la montaña oscurecida
+    pub fn peak(&self) -> Color {
+        self.mountain.blend_subtract(&Color::new(50, 50, 50))
+    }
+}
+
+/// Umbrales de ruido que separan los terrenos, de menor a mayor altitud
+pub struct ThresholdStack {
+    pub ocean: f32,
+    pub mountain: f32,
+    pub continent: f32,
+    pub snow: f32,
+}
+
+impl ThresholdStack {
+    /// Clasifica un valor de ruido de terreno en un color de la paleta, sin la nieve
+    pub fn classify(&self, palette: &BiomePalette, value: f32) -> Color {
+        if value < self.ocean {
+            palette.ocean
+        } else if value < self.mountain {
+            palette.mountain
+        } else if value < self.continent {
+            palette.continent
+        } else {
+            palette.peak()
+        }
+    }
+
+    /// Cantidad de nieve en [0, 1] en regiones de alta altitud
+    pub fn snow_cover(&self, value: f32) -> f32 {
+        if value > self.snow {
+            ((value - self.snow) / (1.0 - self.snow)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Biomas terrestres, clasificados por temperatura y humedad
+#[derive(Clone, Copy)]
+pub enum Biome {
+    Ice,
+    Tundra,
+    Desert,
+    Forest,
+    Jungle,
+}
+
+impl Biome {
+    pub const ALL: [Biome; 5] = [Biome::Ice, Biome::Tundra, Biome::Desert, Biome::Forest, Biome::Jungle];
+
+    /// Posición del bioma en el plano (temperatura, humedad), ambos en [0, 1]
+    pub fn climate(self) -> (f32, f32) {
+        match self {
+            Biome::Ice => (0.0, 0.5),
+            Biome::Tundra => (0.3, 0.45),
+            Biome::Desert => (0.85, 0.1),
+            Biome::Forest => (0.55, 0.65),
+            Biome::Jungle => (0.9, 0.85),
+        }
+    }
+}
+
+/// Tabla de biomas: colores y parámetros de clima
+pub struct BiomeTable {
+    pub ice: Color,
+    pub tundra: Color,
+    pub desert: Color,
+    pub forest: Color,
+    pub jungle: Color,
+    pub moisture_scale: f32,  // Escala del canal de ruido de humedad
+    pub lapse_rate: f32,      // Enfriamiento por altitud
+    pub blend_width: f32,     // Anchura de la transición entre biomas
+}
+
+impl BiomeTable {
+    pub fn color(&self, biome: Biome) -> Color {
+        match biome {
+            Biome::Ice => self.ice,
+            Biome::Tundra => self.tundra,
+            Biome::Desert => self.desert,
+            Biome::Forest => self.forest,
+            Biome::Jungle => self.jungle,
+        }
+    }
+
+    /// Temperatura en [0, 1]: cálida en el ecuador, fría en los polos y en altura
+    pub fn temperature(&self, latitude: f32, elevation: f32) -> f32 {
+        let equator_warmth = 1.0 - latitude.abs().clamp(0.0, 1.0).powf(1.5);
+        (equator_warmth - elevation.max(0.0) * self.lapse_rate).clamp(0.0, 1.0)
+    }
+
+    /// Humedad en [0, 1] a partir de un canal de ruido independiente del de elevación
+    pub fn moisture(&self, uniforms: &Uniforms, position: &Vec3) -> f32 {
+        // Desplazamiento grande para descorrelacionar la humedad de la elevación
+        let offset = 137.0;
+        let value = uniforms.noise.get_noise_3d(
+            position.x * self.moisture_scale + offset,
+            position.y * self.moisture_scale + offset,
+            position.z * self.moisture_scale + offset,
+        );
+        ((value + 1.0) * 0.5).clamp(0.0, 1.0)
+    }
+
+    /// Mezcla suave de los colores de todos los biomas, ponderados por su cercanía climática
+    pub fn blend(&self, temperature: f32, moisture: f32) -> Color {
+        let mut weights = [0.0; 5];
+        let mut total = 0.0;
+        for (weight, biome) in weights.iter_mut().zip(Biome::ALL) {
+            let (t, m) = biome.climate();
+            let distance_sq = (temperature - t).powi(2) + (moisture - m).powi(2);
+            *weight = (-distance_sq / (2.0 * self.blend_width * self.blend_width)).exp();
+            total += *weight;
+        }
+
+        let (mut r, mut g, mut b) = (0.0, 0.0, 0.0);
+        for (weight, biome) in weights.iter().zip(Biome::ALL) {
+            let color = self.color(biome);
+            let w = weight / total;
+            r += color.r as f32 * w;
+            g += color.g as f32 * w;
+            b += color.b as f32 * w;
+        }
+
+        Color::new(r.round() as u8, g.round() as u8, b.round() as u8)
+    }
+}
+
+/// Apariencia completa de un planeta rocoso; la atmósfera se dibuja en una pasada aparte
+pub struct TerrainPreset {
+    pub palette: BiomePalette,
+    pub thresholds: ThresholdStack,
+    pub noise_scale: f32, // Frecuencia del ruido que da forma a los continentes
+    pub biomes: Option<BiomeTable>,
+    pub clouds: CloudLayer,
+    pub atmosphere: Option<AtmosphereSettings>,
+    pub rotation_speed: f32,
+}
+
+// Planeta tipo Tierra
+pub const EARTH: TerrainPreset = TerrainPreset {
+    palette: BiomePalette {
+        ocean: Color::new(10, 115, 252),     // Azul para océano
+        mountain: Color::new(97, 77, 63),    // Marrón para montañas
+        continent: Color::new(34, 139, 34),  // Verde para tierra/continente
+        snow: Color::new(255, 250, 250),     // Blanco nieve
+    },
+    thresholds: ThresholdStack { ocean: -0.038, mountain: 0.2, continent: 0.85, snow: 0.05 },
+    noise_scale: 3.0,
+    biomes: Some(BiomeTable {
+        ice: Color::new(235, 242, 250),
+        tundra: Color::new(140, 134, 110),
+        desert: Color::new(222, 190, 130),
+        forest: Color::new(34, 139, 34),
+        jungle: Color::new(18, 96, 30),
+        moisture_scale: 2.0,
+        lapse_rate: 0.6,
+        blend_width: 0.12,
+    }),
+    clouds: CloudLayer {
+        color: Color::new(255, 255, 255),
+        scale: 4.0,
+        rotation_speed: 0.65,
+        evolution_speed: 0.05,
+        coverage: 0.45,
+        density: 0.95,
+        altitude: 0.04,
+        shadow_strength: 0.6,
+    },
+    atmosphere: Some(atmosphere::EARTH_LIKE),
+    rotation_speed: 0.8,
+};
+
+// Planeta de océanos turquesa y nubes verdosas
+pub const VERDANT: TerrainPreset = TerrainPreset {
+    palette: BiomePalette {
+        ocean: Color::new(65, 199, 240),
+        mountain: Color::new(184, 121, 77),
+        continent: Color::new(69, 128, 69),
+        snow: Color::new(219, 219, 200),
+    },
+    thresholds: ThresholdStack { ocean: -0.038, mountain: 0.2, continent: 0.85, snow: 0.05 },
+    noise_scale: 3.0,
+    biomes: None,
+    clouds: CloudLayer {
+        color: Color::new(139, 232, 190),
+        scale: 4.0,
+        rotation_speed: 0.5,
+        evolution_speed: 0.05,
+        coverage: 0.5,
+        density: 0.9,
+        altitude: 0.04,
+        shadow_strength: 0.5,
+    },
+    atmosphere: Some(atmosphere::EARTH_LIKE),
+    rotation_speed: 0.8,
+};
+
+// Planeta desértico rojizo con tormentas de polvo
+pub const MARS: TerrainPreset = TerrainPreset {
+    palette: BiomePalette {
+        ocean: Color::new(125, 50, 41),      // Llanuras bajas oscuras
+        mountain: Color::new(128, 20, 13),
+        continent: Color::new(176, 109, 67),
+        snow: Color::new(207, 174, 149),     // Escarcha de polvo
+    },
+    thresholds: ThresholdStack { ocean: -0.038, mountain: 0.2, continent: 0.85, snow: 0.05 },
+    noise_scale: 6.0,
+    biomes: None,
+    clouds: CloudLayer {
+        color: Color::new(255, 193, 122),    // Polvo en suspensión
+        scale: 3.0,
+        rotation_speed: 1.1,
+        evolution_speed: 0.1,
+        coverage: 0.3,
+        density: 0.6,
+        altitude: 0.02,
+        shadow_strength: 0.3,
+    },
+    atmosphere: Some(atmosphere::DUSTY),
+    rotation_speed: 0.8,
+};
+
+/// Ruido de terreno en una posición del cuerpo y la posición girada con la que se muestrea.
+/// Es el mismo valor que clasifica los colores y que desplaza los vértices
+pub fn terrain_noise(uniforms: &Uniforms, preset: &TerrainPreset, position: &Vec3) -> (f32, Vec3) {
+    // Simulación de rotación del terreno
+    let angle = uniforms.time * preset.rotation_speed;
+    let rotated_x = position.x * angle.cos() - position.z * angle.sin();
+    let rotated_z = position.x * angle.sin() + position.z * angle.cos();
+    let rotated_position = Vec3::new(rotated_x, position.y, rotated_z);
+
+    // Ruido 3D para definir el tipo de terreno
+    let value = uniforms.noise.get_noise_3d(
+        rotated_position.x * preset.noise_scale,
+        rotated_position.y * preset.noise_scale,
+        rotated_position.z * preset.noise_scale,
+    );
+    (value, rotated_position)
+}
+
+/// Altura del relieve sobre el nivel del mar; los océanos quedan planos
+pub fn terrain_height(uniforms: &Uniforms, preset: &TerrainPreset, position: &Vec3) -> f32 {
+    let (value, _) = terrain_noise(uniforms, preset, position);
+    (value - preset.thresholds.ocean).max(0.0) * uniforms.displacement
+}
+
+/// Desplaza un vértice a lo largo de su normal según la altura del terreno y recalcula la
+/// normal por diferencias finitas. Devuelve (posición, normal) en espacio del cuerpo
+pub fn displace(uniforms: &Uniforms, preset: &TerrainPreset, position: &Vec3, normal: &Vec3) -> (Vec3, Vec3) {
+    let normal = normal.normalize();
+    let helper = if normal.y.abs() > 0.9 { Vec3::new(1.0, 0.0, 0.0) } else { Vec3::new(0.0, 1.0, 0.0) };
+    let tangent = helper.cross(&normal).normalize();
+    let bitangent = normal.cross(&tangent);
+
+    let epsilon = 0.01;
+    let height = terrain_height(uniforms, preset, position);
+    let slope_t = (terrain_height(uniforms, preset, &(position + tangent * epsilon)) - height) / epsilon;
+    let slope_b = (terrain_height(uniforms, preset, &(position + bitangent * epsilon)) - height) / epsilon;
+
+    let displaced = position + normal * height;
+    let displaced_normal = (normal - tangent * slope_t - bitangent * slope_b).normalize();
+    (displaced, displaced_normal)
+}
+
+/// Shader de terreno común a todos los planetas rocosos
+pub fn terrain_shader(fragment: &Fragment, uniforms: &Uniforms, preset: &TerrainPreset) -> FragmentOutput {
+    let thresholds = &preset.thresholds;
+    let (terrain_noise_value, rotated_position) = terrain_noise(uniforms, preset, &fragment.vertex_position.xyz());
+
+    let mut surface_color = thresholds.classify(&preset.palette, terrain_noise_value);
+    let on_land = terrain_noise_value >= thresholds.ocean;
+
+    // Biomas por latitud, altitud y humedad (la latitud sale de la y en espacio del cuerpo)
+    if let Some(biomes) = &preset.biomes {
+        let latitude = fragment.vertex_position.y / fragment.vertex_position.xyz().magnitude().max(1e-6);
+        let elevation = terrain_noise_value - thresholds.ocean;
+        let temperature = biomes.temperature(latitude, elevation);
+
+        if !on_land {
+            // Casquetes de hielo marino cerca de los polos
+            let sea_ice = smoothstep(0.15, 0.05, temperature);
+            surface_color = surface_color.lerp(&biomes.ice, sea_ice);
+        } else if terrain_noise_value < thresholds.continent {
+            let moisture = biomes.moisture(uniforms, &rotated_position);
+            surface_color = biomes.blend(temperature, moisture);
+        }
+    }
+
+    // La nieve va después de los biomas para que la mezcla no la borre
+    surface_color = surface_color.lerp(&preset.palette.snow, thresholds.snow_cover(terrain_noise_value));
+
+    // Capa de nubes independiente del terreno, en espacio del cuerpo
+    let clouds = &preset.clouds;
+    let direction = fragment.vertex_position.xyz().normalize();
+    let light_dir = body_light_direction(uniforms);
+    let cloud_cover = clouds.cover(uniforms, &direction);
+    let cloud_shadow = clouds.shadow(uniforms, &direction, &light_dir);
+
+    // Iluminación del suelo (con la sombra de las nubes) y de las nubes por encima
+    let ground_color = apply_lighting_with_shadow(surface_color, fragment, uniforms, cloud_shadow);
+    let cloud_color = apply_lighting(clouds.color, fragment, uniforms);
+    surface_color = ground_color.lerp(&cloud_color, cloud_cover);
+
+    // Luces nocturnas (atenuadas por las nubes)
+    let emission = if on_land {
+        night_lights(fragment, uniforms, &rotated_position) * (1.0 - cloud_cover)
+    } else {
+        Color::black()
+    };
+
+    FragmentOutput::new(surface_color, emission)
+}
+
+// Luces de ciudades en el lado nocturno; aparecen gradualmente al cruzar el terminador
+fn night_lights(fragment: &Fragment, uniforms: &Uniforms, position: &Vec3) -> Color {
+    if uniforms.night_lights_intensity <= 0.0 {
+        return Color::black();
+    }
+
+    let light_dir = uniforms.light_direction.normalize();
+    let night_factor = smoothstep(0.1, -0.25, dot(&fragment.normal, &light_dir));
+    if night_factor <= 0.0 {
+        return Color::black();
+    }
+
+    // Ruido de alta frecuencia para agrupar las luces en "ciudades"
+    let city_scale = 45.0;
+    let city_noise = uniforms.noise.get_noise_3d(
+        position.x * city_scale,
+        position.y * city_scale,
+        position.z * city_scale,
+    );
+    let density = smoothstep(0.2, 0.6, city_noise);
+
+    let city_color = Color::new(255, 196, 110); // Amarillo cálido de alumbrado
+    city_color * (density * night_factor * uniforms.night_lights_intensity)
+}
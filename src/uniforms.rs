@@ -5,7 +5,17 @@ use crate::color::Color;
 use std::sync::Arc;
 use fastnoise_lite::FastNoiseLite;
 use nalgebra_glm::Mat4;
+use crate::atmosphere::AtmosphereSettings;
+use crate::star::{self, StarSettings};
+use crate::rings::RingProfile;
+use crate::comet::CometState;
+use crate::craters::CraterField;
+use crate::terrain::TerrainPreset;
+use crate::shadows::Occluder;
+use crate::shadow_map::ShadowMap;
+use crate::projection::LogDepth;
 
+#[derive(Clone)]
 pub struct Uniforms {
     pub model_matrix: Mat4,
     pub view_matrix: Mat4,
@@ -14,21 +24,36 @@ pub struct Uniforms {
     pub time: f32,
     pub noise: Arc<FastNoiseLite>,
     pub light_direction: Vec3,
-    pub noise_scale: f32,
-    pub ocean_threshold: f32,
-    pub continent_threshold: f32,
-    pub mountain_threshold: f32,
-    pub snow_threshold: f32,
     pub ring_inner_radius: f32,
     pub ring_outer_radius: f32,
     pub ring_color: Color,
     pub ring_opacity: f32,
     pub ring_frequency: f32,
     pub ring_wave_speed: f32,
-    pub ring_rotation_matrix: Mat4,
+    pub ring_profile: Option<Arc<RingProfile>>,
+    pub ring_normal: Vec3, // Normal del plano de los anillos, en espacio del cuerpo
+    pub light_color: Color,
+    pub ambient_intensity: f32,
+    pub ambient_color: Color,
+    pub night_lights_intensity: f32,
+    pub camera_position: Vec3,
+    pub atmosphere: Option<AtmosphereSettings>,
+    pub detail_noise: Arc<FastNoiseLite>, // Ruido secundario (p. ej. granulación estelar)
+    pub star: Option<StarSettings>,
+    pub comet: Option<CometState>,
+    pub instance_seed: u32,             // Semilla de la instancia que se está dibujando
+    pub light_position: Option<Vec3>,   // Luz puntual en espacio del mundo (la estrella de un sistema)
+    pub craters: Option<Arc<CraterField>>,
+    pub terrain: Option<&'static TerrainPreset>, // Terreno que desplaza los vértices
+    pub displacement: f32,                       // Escala del relieve; 0 = esfera sin desplazar
+    pub occluders: Vec<Occluder>,                // Otros cuerpos que pueden eclipsar la luz
+    pub light_angular_radius: f32,               // Radio angular de la estrella (rad); ancho de la penumbra
+    pub shadow_map: Option<Arc<ShadowMap>>,
+    pub log_depth: Option<LogDepth>,             // Profundidad logarítmica en lugar de la de la proyección
 }
 
 impl Uniforms {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         model_matrix: Mat4,
         view_matrix: Mat4,
@@ -37,18 +62,12 @@ impl Uniforms {
         time: f32,
         noise: Arc<FastNoiseLite>,
         light_direction: Vec3,
-        noise_scale: f32,
-        ocean_threshold: f32,
-        continent_threshold: f32,
-        mountain_threshold: f32,
-        snow_threshold: f32,
         ring_inner_radius: f32,
         ring_outer_radius: f32,
         ring_color: Color,
         ring_opacity: f32,
         ring_frequency: f32,
         ring_wave_speed: f32,
-        ring_rotation_matrix: Mat4,
     ) -> Self {
         Uniforms {
             model_matrix,
@@ -56,20 +75,34 @@ impl Uniforms {
             projection_matrix,
             viewport_matrix,
             time,
+            detail_noise: noise.clone(),
             noise,
             light_direction,
-            noise_scale,
-            ocean_threshold,
-            continent_threshold,
-            mountain_threshold,
-            snow_threshold,
             ring_inner_radius,
             ring_outer_radius,
             ring_color,
             ring_opacity,
             ring_frequency,
             ring_wave_speed,
-            ring_rotation_matrix,
+            ring_profile: None,
+            ring_normal: Vec3::new(0.0, 1.0, 0.0),
+            light_color: Color::from_temperature(star::SUN.temperature), // Luz de la estrella del sistema
+            ambient_intensity: 0.06,
+            ambient_color: Color::new(180, 190, 255), // Luz ambiental ligeramente azulada
+            night_lights_intensity: 0.0,              // Sin luces nocturnas por defecto
+            camera_position: Vec3::zeros(),
+            atmosphere: None,
+            star: None,
+            comet: None,
+            instance_seed: 0,
+            light_position: None,
+            craters: None,
+            terrain: None,
+            displacement: 0.0,
+            occluders: Vec::new(),
+            light_angular_radius: 0.05, // Exagerado respecto al Sol real para que la penumbra se vea
+            shadow_map: None,
+            log_depth: None,
         }
     }
 }
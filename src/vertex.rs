@@ -1,4 +1,4 @@
-use nalgebra_glm::{Vec3, Vec2};
+use nalgebra_glm::{Vec3, Vec2, Vec4};
 use crate::color::Color;
 #[derive(Clone)]
 pub struct Vertex {
@@ -8,6 +8,7 @@ pub struct Vertex {
     pub color: Color,
     pub transformed_position: Vec3,
     pub transformed_normal: Vec3,
+    pub clip_position: Vec4, // Posición tras la proyección, antes de dividir por w
 }
 
 impl Vertex {
@@ -19,6 +20,7 @@ impl Vertex {
             color: Color::black(),
             transformed_position: Vec3::zeros(),
             transformed_normal: Vec3::zeros(),
+            clip_position: Vec4::zeros(),
         }
     }
 }